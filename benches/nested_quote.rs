@@ -0,0 +1,36 @@
+//! Measures the cost of cloning a deeply nested `Quote`, the workload that
+//! motivated making `Quote`'s internals `Arc`-backed. Run with
+//! `cargo bench --bench nested_quote` once the crate has a manifest.
+
+use dt_tool::rail_machine::{RailVal, Quote};
+use std::time::Instant;
+
+fn nested_quote(depth: usize) -> Quote {
+    let mut quote = Quote::new().push_i64(0);
+    for _ in 0..depth {
+        quote = Quote::new().push_quote(quote);
+    }
+    quote
+}
+
+fn main() {
+    let quote = nested_quote(5_000);
+
+    let start = Instant::now();
+    let mut clones = Vec::with_capacity(10_000);
+    for _ in 0..10_000 {
+        clones.push(quote.clone());
+    }
+    let elapsed = start.elapsed();
+
+    // Touch the clones so the loop above can't be optimized away.
+    let survivors = clones
+        .iter()
+        .filter(|q| matches!(q.values.first(), Some(RailVal::Quote(_))))
+        .count();
+
+    println!(
+        "10000 clones of a {}-deep nested quote took {:?} ({} non-empty)",
+        5_000, elapsed, survivors
+    );
+}
@@ -1,20 +1,108 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use dt_tool::dt_machine::DtState;
-use dt_tool::{loading, DT_VERSION};
+use dt_tool::rail_machine::{verify_quote, Quote, RailVal, SyntaxRule, SyntaxTable};
+use dt_tool::{corelib, loading, prompt, DT_VERSION};
 
 pub fn main() {
     let args = DtEvaluator::parse();
 
-    let state = DtState::new_with_libs(args.no_stdlib, args.lib_list);
+    if let Some(Command::Check { file }) = &args.command {
+        check(file);
+        return;
+    }
 
-    let tokens = loading::from_dt_source(args.dt_code.join(" "));
-    state.run_tokens(tokens);
+    let state = match &args.resume {
+        Some(path) => {
+            let snapshot = std::fs::read_to_string(path)
+                .unwrap_or_else(|err| panic!("Could not read snapshot \"{}\": {}", path, err));
+            DtState::from_snapshot(&snapshot)
+                .unwrap_or_else(|err| panic!("Could not restore snapshot \"{}\": {}", path, err))
+        }
+        None => DtState::new_with_libs(args.no_stdlib, args.lib_list),
+    };
+
+    let state = if args.dt_code.is_empty() {
+        prompt::run(state)
+    } else {
+        let dt_code = args.dt_code.join(" ");
+        let tokens = loading::from_dt_source(&dt_code);
+        state.run_tokens(&dt_code, tokens)
+    };
+
+    if let Some(path) = &args.dump {
+        std::fs::write(path, state.to_snapshot())
+            .unwrap_or_else(|err| panic!("Could not write snapshot \"{}\": {}", path, err));
+    }
+}
+
+/// Statically verifies `file`'s top-level stack effect without running it,
+/// printing the inferred signature or the first conflict found.
+fn check(file: &str) {
+    let source = std::fs::read_to_string(file)
+        .unwrap_or_else(|err| panic!("Could not read \"{}\": {}", file, err));
+    let tokens = loading::from_dt_source(&source);
+    let quote = parse_for_check(&tokens);
+    let dictionary = corelib::new_dictionary();
+
+    match verify_quote(&quote, &dictionary) {
+        Ok(effect) => {
+            println!("{} -> {}", effect.consumes.join(" "), effect.produces.join(" "));
+        }
+        Err(err) => {
+            eprintln!("{}", loading::render_diagnostic(&source, err.span(), &err.to_string()));
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Parses `tokens` into the one `Quote` `check` verifies, re-consulting a
+/// `SyntaxTable` one top-level term at a time the same way
+/// `DtState::run_tokens` does, so a `def-syntax` registered earlier in the
+/// file is visible to parsing what follows it — unlike plain `loading::parse`,
+/// which fixes `syntax` for the whole call and so never sees a marker the
+/// file registers itself.
+///
+/// `check` never runs the program, so a literal `"marker" "terminator"
+/// def-syntax` term is applied to `syntax` directly here rather than via the
+/// `def-syntax` builtin: it's a pure table update with no other observable
+/// effect, so recognizing it statically doesn't compromise "verify without
+/// running".
+fn parse_for_check(tokens: &[loading::Token]) -> Quote {
+    let mut syntax = SyntaxTable::new();
+    let mut quote = Quote::new();
+    let mut remaining = tokens;
+
+    while let (Some(term), rest) = loading::parse_one_term(remaining, &syntax) {
+        remaining = rest;
+
+        if let [(RailVal::Command(op), _)] = term.as_slice() {
+            if &**op == "def-syntax" {
+                if let [.., RailVal::String(marker), RailVal::String(terminator)] = quote.values.as_slice() {
+                    syntax.insert(
+                        marker.clone(),
+                        SyntaxRule {
+                            terminator: terminator.clone(),
+                        },
+                    );
+                }
+            }
+        }
+
+        for (value, span) in term {
+            quote = quote.push_spanned(value, span);
+        }
+    }
+
+    quote
 }
 
 #[derive(Parser)]
 #[clap(name = "dt", version = DT_VERSION)]
 /// dt evaluator. It's duck tape for your unix pipes
 struct DtEvaluator {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     #[clap(long)]
     /// Disable loading the dt standard library.
     no_stdlib: bool,
@@ -23,6 +111,25 @@ struct DtEvaluator {
     /// A file containing a line-separated list of library paths to preload.
     lib_list: Option<String>,
 
+    #[clap(long)]
+    /// Resume a session from a snapshot file written by `--dump`, instead of
+    /// starting fresh.
+    resume: Option<String>,
+
+    #[clap(long)]
+    /// Dump the session's state to a snapshot file after running, so a later
+    /// invocation can pick up where this one left off with `--resume`.
+    dump: Option<String>,
+
     /// Code to evaluate
     dt_code: Vec<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Statically verify a file's stack effect without running it.
+    Check {
+        /// Path to the `.dt` file to verify.
+        file: String,
+    },
 }
\ No newline at end of file
@@ -0,0 +1,45 @@
+use crate::rail_machine::RailDef;
+
+pub fn builtins() -> Vec<RailDef<'static>> {
+    let defs = vec![
+        RailDef::on_stack("+", &["*", "*"], &["*"], |stack| {
+            let (b, stack) = stack.pop_numeric("+")?;
+            let (a, stack) = stack.pop_numeric("+")?;
+            Ok(stack.push_numeric(a.checked_add(b)?))
+        }),
+        RailDef::on_stack("-", &["*", "*"], &["*"], |stack| {
+            let (b, stack) = stack.pop_numeric("-")?;
+            let (a, stack) = stack.pop_numeric("-")?;
+            Ok(stack.push_numeric(a.checked_sub(b)?))
+        }),
+        RailDef::on_stack("*", &["*", "*"], &["*"], |stack| {
+            let (b, stack) = stack.pop_numeric("*")?;
+            let (a, stack) = stack.pop_numeric("*")?;
+            Ok(stack.push_numeric(a.checked_mul(b)?))
+        }),
+        RailDef::on_stack("/", &["*", "*"], &["*"], |stack| {
+            let (b, stack) = stack.pop_numeric("/")?;
+            let (a, stack) = stack.pop_numeric("/")?;
+            Ok(stack.push_numeric(a.checked_div(b)?))
+        }),
+    ];
+
+    #[cfg(feature = "decimal")]
+    let defs = {
+        let mut defs = defs;
+        defs.push(RailDef::on_stack(">decimal", &["*", "i64"], &["decimal"], |stack| {
+            let (scale, stack) = stack.pop_i64(">decimal")?;
+            let (n, stack) = stack.pop_numeric(">decimal")?;
+            let scale = u32::try_from(scale).map_err(|_| {
+                crate::rail_machine::RailError::Arithmetic(format!(
+                    "\">decimal\" scale must not be negative, got {}",
+                    scale
+                ))
+            })?;
+            Ok(stack.push_numeric(n.to_decimal(scale)))
+        }));
+        defs
+    };
+
+    defs
+}
@@ -1,28 +1,42 @@
 use crate::rail_machine::{run_quote, RailDef};
 
 pub fn builtins() -> Vec<RailDef<'static>> {
-    vec![RailDef::on_state("opt", &["seq"], &[], |state| {
-        // TODO: All conditions and all actions must have the same stack effect.
-        let (mut options, stack) = state.stack.clone().pop_quote("opt");
-        let mut state = state.replace_stack(stack);
+    vec![
+        RailDef::on_state("opt", &["quote"], &[], |state| {
+            // TODO: All conditions and all actions must have the same stack effect.
+            let (mut options, stack) = state.stack.clone().pop_quote("opt")?;
+            let mut state = state.replace_stack(stack);
 
-        options.values.reverse();
+            options = options.reverse();
 
-        while !options.is_empty() {
-            let (condition, opts) = options.pop_quote("opt");
-            let (action, opts) = opts.pop_quote("opt");
-            options = opts;
+            while !options.is_empty() {
+                let (condition, opts) = options.pop_quote("opt")?;
+                let (action, opts) = opts.pop_quote("opt")?;
+                options = opts;
 
-            // TODO: Should this be running in a way that can't alter the main stack?
-            state = run_quote(&condition, state);
-            let (success, stack) = state.stack.clone().pop_bool("opt");
-            state = state.replace_stack(stack);
+                // TODO: Should this be running in a way that can't alter the main stack?
+                state = run_quote(&condition, state)?;
+                let (success, stack) = state.stack.clone().pop_bool("opt")?;
+                state = state.replace_stack(stack);
 
-            if success {
-                return run_quote(&action, state);
+                if success {
+                    return run_quote(&action, state);
+                }
             }
-        }
 
-        state
-    })]
+            Ok(state)
+        }),
+        RailDef::on_state("try", &["quote"], &["..."], |state| {
+            // Run the quote on the stack left after popping it, so a roll
+            // back on error restores exactly the stack the quote would have
+            // seen, not the one with the quote itself still sitting on top.
+            let (quot, rest) = state.stack.clone().pop_quote("try")?;
+            let snapshot = state.clone().replace_stack(rest.clone());
+
+            match run_quote(&quot, state.replace_stack(rest)) {
+                Ok(new_state) => Ok(new_state),
+                Err(err) => Ok(snapshot.update_stack(|stack| stack.push_string(err.to_string()))),
+            }
+        }),
+    ]
 }
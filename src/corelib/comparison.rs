@@ -0,0 +1,46 @@
+use crate::rail_machine::RailDef;
+
+pub fn builtins() -> Vec<RailDef<'static>> {
+    vec![
+        RailDef::on_stack("not", &["bool"], &["bool"], |stack| {
+            let (a, stack) = stack.pop_bool("not")?;
+            Ok(stack.push_bool(!a))
+        }),
+        RailDef::on_stack("==", &["*", "*"], &["bool"], |stack| {
+            let (b, stack) = stack.pop()?;
+            let (a, stack) = stack.pop()?;
+            Ok(stack.push_bool(a == b))
+        }),
+        RailDef::on_stack("!=", &["*", "*"], &["bool"], |stack| {
+            let (b, stack) = stack.pop()?;
+            let (a, stack) = stack.pop()?;
+            Ok(stack.push_bool(a != b))
+        }),
+        RailDef::on_stack("<", &["*", "*"], &["bool"], |stack| {
+            let (b, stack) = stack.pop_numeric("<")?;
+            let (a, stack) = stack.pop_numeric("<")?;
+            Ok(stack.push_bool(a.partial_cmp(b) == Some(std::cmp::Ordering::Less)))
+        }),
+        RailDef::on_stack("<=", &["*", "*"], &["bool"], |stack| {
+            let (b, stack) = stack.pop_numeric("<=")?;
+            let (a, stack) = stack.pop_numeric("<=")?;
+            Ok(stack.push_bool(matches!(
+                a.partial_cmp(b),
+                Some(std::cmp::Ordering::Less | std::cmp::Ordering::Equal)
+            )))
+        }),
+        RailDef::on_stack(">", &["*", "*"], &["bool"], |stack| {
+            let (b, stack) = stack.pop_numeric(">")?;
+            let (a, stack) = stack.pop_numeric(">")?;
+            Ok(stack.push_bool(a.partial_cmp(b) == Some(std::cmp::Ordering::Greater)))
+        }),
+        RailDef::on_stack(">=", &["*", "*"], &["bool"], |stack| {
+            let (b, stack) = stack.pop_numeric(">=")?;
+            let (a, stack) = stack.pop_numeric(">=")?;
+            Ok(stack.push_bool(matches!(
+                a.partial_cmp(b),
+                Some(std::cmp::Ordering::Greater | std::cmp::Ordering::Equal)
+            )))
+        }),
+    ]
+}
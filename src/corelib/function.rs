@@ -1,46 +1,58 @@
-use crate::rail_machine::{run_quot, RailDef};
+use crate::rail_machine::{run_quote, RailDef, RailError, SyntaxRule};
+use std::sync::Arc;
 
 pub fn builtins() -> Vec<RailDef<'static>> {
     vec![
-        RailDef::on_state("do", &["quot"], &["..."], |state| {
-            let (quot, stack) = state.stack.clone().pop_quotation("do");
+        RailDef::on_state("do", &["quote"], &["..."], |state| {
+            let (quot, stack) = state.stack.clone().pop_quote("do")?;
             let state = state.replace_stack(stack);
-            run_quot(&quot, state)
+            run_quote(&quot, state)
         }),
-        RailDef::on_state("doin", &["quot", "quot"], &["quot"], |state| {
-            state.clone().update_stack(|stack| {
-                let (quot, stack) = stack.pop_quotation("call-in");
-                let (working_stack, stack) = stack.pop_quotation("call-in");
+        RailDef::on_state("doin", &["quote", "quote"], &["quote"], |state| {
+            state.clone().try_update_stack(|stack| {
+                let (quot, stack) = stack.pop_quote("call-in")?;
+                let (working_stack, stack) = stack.pop_quote("call-in")?;
 
                 let substate = state.contextless_child(working_stack); // TODO: Really just need dictionary.
-                let substate = run_quot(&quot, substate);
+                let substate = run_quote(&quot, substate)?;
 
-                stack.push_quotation(substate.stack)
+                Ok(stack.push_quote(substate.stack))
             })
         }),
-        RailDef::on_state("def", &["quot", "s"], &[], |state| {
-            state.update_stack_and_dict(|stack, dictionary| {
+        RailDef::on_state("def", &["quote", "string"], &[], |state| {
+            state.try_update_stack_and_dict(|stack, dictionary| {
                 let mut dictionary = dictionary;
-                let (name, stack) = stack.pop_string("def");
-                let (quot, stack) = stack.pop_quotation("def");
-                dictionary.insert(name.clone(), RailDef::from_quot(&name, quot));
-                (stack, dictionary)
+                let (name, stack) = stack.pop_string("def")?;
+                let (quot, stack) = stack.pop_quote("def")?;
+                let def = RailDef::from_quote(&name, quot, &dictionary)?;
+                dictionary.insert(name, Arc::new(def));
+                Ok((stack, dictionary))
             })
         }),
-        RailDef::on_state("def?", &["s"], &["bool"], |state| {
-            state.clone().update_stack(|stack| {
-                let (name, stack) = stack.pop_string("def?");
-                stack.push_bool(state.dictionary.contains_key(&name))
+        RailDef::on_state("def-syntax", &["string", "string"], &[], |state| {
+            state.try_update_stack_and_syntax(|stack, syntax| {
+                let mut syntax = syntax;
+                let (terminator, stack) = stack.pop_string("def-syntax")?;
+                let (marker, stack) = stack.pop_string("def-syntax")?;
+                syntax.insert(marker, SyntaxRule { terminator });
+                Ok((stack, syntax))
             })
         }),
-        RailDef::on_state("undef", &["s"], &[], |state| {
-            state.update_stack_and_dict(|stack, dictionary| {
+        RailDef::on_state("def?", &["string"], &["bool"], |state| {
+            state.clone().try_update_stack(|stack| {
+                let (name, stack) = stack.pop_string("def?")?;
+                Ok(stack.push_bool(state.dictionary.contains_key(&name)))
+            })
+        }),
+        RailDef::on_state("undef", &["string"], &[], |state| {
+            state.try_update_stack_and_dict(|stack, dictionary| {
                 let mut dictionary = dictionary;
-                let (name, stack) = stack.pop_string("undef");
-                dictionary.remove(&name).unwrap_or_else(|| {
-                    panic!("Cannot undef \"{}\", it was already undefined", name)
-                });
-                (stack, dictionary)
+                let (name, stack) = stack.pop_string("undef")?;
+                dictionary.remove(&name).ok_or_else(|| RailError::Undefined {
+                    name: name.clone(),
+                    span: crate::loading::Span::unknown(),
+                })?;
+                Ok((stack, dictionary))
             })
         }),
     ]
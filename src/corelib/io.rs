@@ -0,0 +1,84 @@
+//! Operators that bridge the process boundary: reading from and writing to
+//! the real stdin/stdout/stderr, so `dt` can sit in the middle of a shell
+//! pipeline instead of only ever evaluating its command-line args.
+
+use crate::rail_machine::{run_quote, RailDef, RailError, RailVal};
+use std::io::{self, BufRead, Write};
+
+pub fn builtins() -> Vec<RailDef<'static>> {
+    vec![
+        RailDef::on_stack("read-line", &[], &["string", "bool"], |stack| {
+            let mut line = String::new();
+            let bytes_read = io::stdin()
+                .lock()
+                .read_line(&mut line)
+                .map_err(|err| RailError::Io(err.to_string()))?;
+            let eof = bytes_read == 0;
+            strip_newline(&mut line);
+            Ok(stack.push_string(line).push_bool(eof))
+        }),
+        RailDef::on_stack("read-all", &[], &["string"], |stack| {
+            use std::io::Read;
+            let mut contents = String::new();
+            io::stdin()
+                .lock()
+                .read_to_string(&mut contents)
+                .map_err(|err| RailError::Io(err.to_string()))?;
+            Ok(stack.push_string(contents))
+        }),
+        RailDef::on_state("each-line", &["quote"], &["..."], |state| {
+            let (quot, stack) = state.stack.clone().pop_quote("each-line")?;
+            let mut state = state.replace_stack(stack);
+
+            let stdin = io::stdin();
+            for line in stdin.lock().lines() {
+                let line = line.map_err(|err| RailError::Io(err.to_string()))?;
+                state = state.update_stack(|stack| stack.push_string(line.clone()));
+                state = run_quote(&quot, state)?;
+                io::stdout()
+                    .flush()
+                    .map_err(|err| RailError::Io(err.to_string()))?;
+            }
+
+            Ok(state)
+        }),
+        RailDef::on_stack("print", &["*"], &[], |stack| {
+            let (term, stack) = stack.pop()?;
+            print!("{}", display_for_print(&term));
+            Ok(stack)
+        }),
+        RailDef::on_stack("println", &["*"], &[], |stack| {
+            let (term, stack) = stack.pop()?;
+            println!("{}", display_for_print(&term));
+            Ok(stack)
+        }),
+        RailDef::on_stack("eprint", &["*"], &[], |stack| {
+            let (term, stack) = stack.pop()?;
+            eprint!("{}", display_for_print(&term));
+            Ok(stack)
+        }),
+    ]
+}
+
+/// Strips a trailing `\n` (and a preceding `\r`, for CRLF input) the way
+/// `BufRead::read_line` leaves it, so `read-line` hands back just the line's
+/// own text.
+fn strip_newline(line: &mut String) {
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+}
+
+/// `RailVal`'s `Display` wraps strings in quotes, which is right for showing
+/// a value back on the stack but wrong for a sink meant to feed the rest of
+/// a shell pipeline raw text — there, a `String` should print as its own
+/// contents.
+fn display_for_print(val: &RailVal) -> String {
+    match val {
+        RailVal::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
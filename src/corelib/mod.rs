@@ -0,0 +1,27 @@
+//! `dt`'s standard library of builtin words, grouped by concern and
+//! assembled into the dictionary every `RailState` starts with.
+
+pub mod arithmetic;
+pub mod choice;
+pub mod comparison;
+pub mod function;
+pub mod io;
+
+use crate::rail_machine::Dictionary;
+use std::sync::Arc;
+
+pub fn new_dictionary() -> Dictionary {
+    let mut dictionary = Dictionary::new();
+
+    for def in arithmetic::builtins()
+        .into_iter()
+        .chain(choice::builtins())
+        .chain(comparison::builtins())
+        .chain(function::builtins())
+        .chain(io::builtins())
+    {
+        dictionary.insert(def.name.clone(), Arc::new(def));
+    }
+
+    dictionary
+}
@@ -0,0 +1,119 @@
+//! The `dt` binary's session state: builds a `RailState` from CLI flags,
+//! runs a token stream against it, and (de)serializes the whole thing to a
+//! JSON snapshot so a pipeline can pick up where a previous invocation left
+//! off. See `rail_machine::RailStateSnapshot` for exactly what does and
+//! doesn't survive a round trip.
+
+use crate::loading::{self, Token};
+use crate::rail_machine::{report_error, Context, Dictionary, RailState};
+use std::fs;
+
+pub struct DtState {
+    state: RailState,
+}
+
+impl DtState {
+    pub fn new() -> DtState {
+        DtState {
+            state: RailState::new(Context::Main),
+        }
+    }
+
+    /// Builds a session's starting dictionary: the full builtin word set,
+    /// unless `no_stdlib` opts out of it, then whatever `.dt` libraries
+    /// `lib_list` names (one path per line), loaded in order.
+    pub fn new_with_libs(no_stdlib: bool, lib_list: Option<String>) -> DtState {
+        let mut dt_state = DtState::new();
+
+        if no_stdlib {
+            dt_state.state = RailState {
+                dictionary: Dictionary::new(),
+                ..dt_state.state
+            };
+        }
+
+        if let Some(lib_list_path) = lib_list {
+            dt_state = dt_state.load_libs(&lib_list_path);
+        }
+
+        dt_state
+    }
+
+    fn load_libs(mut self, lib_list_path: &str) -> DtState {
+        let list = fs::read_to_string(lib_list_path)
+            .unwrap_or_else(|err| panic!("Could not read lib list \"{}\": {}", lib_list_path, err));
+
+        for lib_path in list.lines().filter(|line| !line.trim().is_empty()) {
+            let source = fs::read_to_string(lib_path)
+                .unwrap_or_else(|err| panic!("Could not read library \"{}\": {}", lib_path, err));
+            self = self.run_source(&source);
+        }
+
+        self
+    }
+
+    /// Tokenizes and runs `source` in one step, keeping it around as
+    /// `state.source` for diagnostics.
+    pub fn run_source(self, source: &str) -> DtState {
+        let tokens = loading::from_dt_source(source);
+        self.run_tokens(source, tokens)
+    }
+
+    /// Runs an already-tokenized program against this session, one top-level
+    /// term at a time: each term is parsed against the *current* `syntax`
+    /// table and run before the next term is parsed, so a `def-syntax`
+    /// registered earlier in `tokens` can affect how the rest of `tokens` is
+    /// parsed — unlike `loading::parse`, which fixes `syntax` for the whole
+    /// call. `source` is the exact text `tokens` came from, kept around so a
+    /// derail can render a diagnostic against it; a failing run reports the
+    /// error and leaves the session's pre-run state untouched entirely, the
+    /// same as a failing `try`.
+    pub fn run_tokens(self, source: &str, tokens: Vec<Token>) -> DtState {
+        let original_state = self.state.with_source(source);
+        let mut state = original_state.clone();
+        let mut remaining = tokens.as_slice();
+
+        loop {
+            let (term, rest) = loading::parse_one_term(remaining, &state.syntax);
+            remaining = rest;
+            let Some(term) = term else { break };
+
+            let mut quote = crate::rail_machine::Quote::new();
+            for (value, span) in term {
+                quote = quote.push_spanned(value, span);
+            }
+
+            state = match crate::rail_machine::run_quote(&quote, state) {
+                Ok(new_state) => new_state,
+                Err(err) => {
+                    report_error(&original_state, &err);
+                    return DtState { state: original_state };
+                }
+            };
+        }
+
+        DtState { state }
+    }
+
+    /// Captures this session's stack, source, and user-defined words so it
+    /// can be restored later with `from_snapshot`.
+    pub fn to_snapshot(&self) -> String {
+        serde_json::to_string(&self.state.to_snapshot())
+            .expect("a RailStateSnapshot should always serialize")
+    }
+
+    /// Restores a session from a snapshot produced by `to_snapshot`, on top
+    /// of a freshly built dictionary of builtins.
+    pub fn from_snapshot(snapshot: &str) -> Result<DtState, serde_json::Error> {
+        let snapshot = serde_json::from_str(snapshot)?;
+        Ok(DtState {
+            state: RailState::from_snapshot(snapshot),
+        })
+    }
+}
+
+impl Default for DtState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
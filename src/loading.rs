@@ -0,0 +1,281 @@
+//! Turns raw `dt` source text into spanned tokens.
+//!
+//! This is intentionally a thin, whitespace-driven tokenizer: `dt` programs
+//! are space-separated terms, with `"..."` strings and `[ ... ]` quotations
+//! as the only multi-token constructs. Each token keeps the byte offsets it
+//! came from so later passes (diagnostics, `def-syntax`) can point back at
+//! the exact source slice.
+
+use serde::{Deserialize, Serialize};
+
+/// A byte-offset range into a piece of source text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// A span with no real source location, used for values that were never
+    /// read from source text (e.g. results pushed by builtins).
+    pub fn unknown() -> Self {
+        Span { start: 0, end: 0 }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        self.start == 0 && self.end == 0
+    }
+}
+
+/// A single raw token plus the span of source text it was lexed from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub text: String,
+    pub span: Span,
+}
+
+/// Tokenizes `dt` source into a flat stream of spanned tokens.
+///
+/// Strings (`"like this"`) are lexed as a single token including the quotes;
+/// everything else is split on whitespace, with `[` and `]` always treated
+/// as their own token even when jammed up against neighbouring text.
+pub fn from_dt_source(source: &str) -> Vec<Token> {
+    let len = source.len();
+    let mut chars = source.char_indices().peekable();
+    let mut tokens = vec![];
+
+    while let Some(&(i, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        if c == '"' {
+            let start = i;
+            chars.next();
+            while let Some(&(_, c)) = chars.peek() {
+                if c == '"' {
+                    break;
+                }
+                if c == '\\' {
+                    chars.next();
+                }
+                chars.next();
+            }
+            let end = match chars.next() {
+                Some((i, _)) => i + 1, // consume closing quote
+                None => len,
+            };
+            tokens.push(Token {
+                text: source[start..end].to_string(),
+                span: Span::new(start, end),
+            });
+            continue;
+        }
+
+        if c == '[' || c == ']' {
+            chars.next();
+            tokens.push(Token {
+                text: c.to_string(),
+                span: Span::new(i, i + c.len_utf8()),
+            });
+            continue;
+        }
+
+        let start = i;
+        let mut end = len;
+        while let Some(&(i, c)) = chars.peek() {
+            if c.is_whitespace() || c == '[' || c == ']' {
+                end = i;
+                break;
+            }
+            chars.next();
+        }
+        tokens.push(Token {
+            text: source[start..end].to_string(),
+            span: Span::new(start, end),
+        });
+    }
+
+    tokens
+}
+
+/// Parses a flat token stream into a runnable `Quote`, consulting `syntax`
+/// for `def-syntax` markers one top-level term at a time (see
+/// `parse_one_term`). Turns matched `[` `]` pairs into nested
+/// `RailVal::Quote`s and recognizes numeric, boolean, and string literals
+/// along the way — anything else becomes a `RailVal::Command` to be looked
+/// up in the dictionary at run time.
+///
+/// This always uses the one `syntax` table handed to it for every term, so a
+/// `def-syntax` earlier in the same tokens is never visible to parsing what
+/// comes after it — see `DtState::run_tokens` and `dt check`'s
+/// `parse_for_check`, which call `parse_one_term` directly instead so they
+/// can re-consult `syntax` after each term.
+pub fn parse(tokens: &[Token], syntax: &crate::rail_machine::SyntaxTable) -> crate::rail_machine::Quote {
+    let mut quote = crate::rail_machine::Quote::new();
+    let mut remaining = tokens;
+
+    while let (Some(term), rest) = parse_one_term(remaining, syntax) {
+        remaining = rest;
+        for (value, span) in term {
+            quote = quote.push_spanned(value, span);
+        }
+    }
+
+    quote
+}
+
+/// The values (and their spans) making up one top-level term, in source
+/// order — more than one entry only for an expanded `def-syntax` marker.
+pub type ParsedTerm = Vec<(crate::rail_machine::RailVal, Span)>;
+
+/// Parses the next top-level term out of `tokens`, expanding it first if it
+/// starts with a `def-syntax` marker registered in `syntax`. Returns the
+/// term's values (more than one only for an expanded marker, which yields
+/// the `[ <body> ] "<name>" def` sequence) alongside whatever of `tokens`
+/// comes after it, or `None` once `tokens` is exhausted.
+///
+/// Unlike `parse`, this only looks at `syntax` once per call instead of
+/// sweeping the whole stream up front, so a caller that runs each term as
+/// it's parsed can pick up a `def-syntax` registered by an earlier term in
+/// the very same script — markers are still only recognized where written
+/// at the top level; nesting inside `[ ... ]` isn't considered.
+pub fn parse_one_term<'t>(
+    mut tokens: &'t [Token],
+    syntax: &crate::rail_machine::SyntaxTable,
+) -> (Option<ParsedTerm>, &'t [Token]) {
+    use crate::rail_machine::RailVal;
+
+    loop {
+        let Some((first, rest)) = tokens.split_first() else {
+            return (None, tokens);
+        };
+
+        if let Some(rule) = syntax.get(&first.text) {
+            let marker_span = first.span;
+            let Some(terminator_pos) = rest.iter().position(|t| t.text == rule.terminator) else {
+                // No terminator anywhere in the rest of the script; nothing
+                // more reading could fix, so leave the marker as a literal
+                // command for the evaluator to flag as undefined.
+                return (Some(vec![(RailVal::Command(std::sync::Arc::from(first.text.as_str())), marker_span)]), rest);
+            };
+
+            let captured = &rest[..terminator_pos];
+            let after = &rest[terminator_pos + 1..];
+
+            let Some((name_token, body_tokens)) = captured.split_first() else {
+                // Nothing captured for this marker to name; drop the
+                // dangling form rather than hand the evaluator a malformed
+                // `def`, and keep scanning after the terminator.
+                tokens = after;
+                continue;
+            };
+
+            let (body, _) = parse_into(body_tokens, crate::rail_machine::Quote::new());
+            return (
+                Some(vec![
+                    (RailVal::Quote(body), marker_span),
+                    (RailVal::String(name_token.text.clone()), name_token.span),
+                    (RailVal::Command(std::sync::Arc::from("def")), marker_span),
+                ]),
+                after,
+            );
+        }
+
+        return match first.text.as_str() {
+            "[" => {
+                let (inner, rest) = parse_into(rest, crate::rail_machine::Quote::new());
+                (Some(vec![(RailVal::Quote(inner), first.span)]), rest)
+            }
+            // A stray close bracket has nothing to match; drop it rather
+            // than handing the evaluator a malformed term.
+            "]" => {
+                tokens = rest;
+                continue;
+            }
+            text => (Some(vec![(parse_term(text), first.span)]), rest),
+        };
+    }
+}
+
+fn parse_into(
+    tokens: &[Token],
+    mut quote: crate::rail_machine::Quote,
+) -> (crate::rail_machine::Quote, &[Token]) {
+    let mut tokens = tokens;
+
+    while let Some((token, rest)) = tokens.split_first() {
+        tokens = rest;
+        match token.text.as_str() {
+            "[" => {
+                let (inner, rest) = parse_into(tokens, crate::rail_machine::Quote::new());
+                tokens = rest;
+                quote = quote.push_spanned(crate::rail_machine::RailVal::Quote(inner), token.span);
+            }
+            "]" => return (quote, tokens),
+            text => quote = quote.push_spanned(parse_term(text), token.span),
+        }
+    }
+
+    (quote, tokens)
+}
+
+fn parse_term(text: &str) -> crate::rail_machine::RailVal {
+    use crate::rail_machine::RailVal;
+
+    if let Ok(n) = text.parse::<i64>() {
+        RailVal::I64(n)
+    } else if let Ok(n) = text.parse::<f64>() {
+        RailVal::F64(n)
+    } else if text == "true" {
+        RailVal::Boolean(true)
+    } else if text == "false" {
+        RailVal::Boolean(false)
+    } else if let Some(inner) = text.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        RailVal::String(inner.to_string())
+    } else {
+        RailVal::Command(std::sync::Arc::from(text))
+    }
+}
+
+/// Finds the 1-indexed line and column that a byte offset falls on within
+/// `source`, along with the full text of that line (no trailing newline).
+pub fn line_col(source: &str, offset: usize) -> (usize, usize, &str) {
+    let mut line_start = 0;
+    let mut line_num = 1;
+
+    for (num, line) in source.split('\n').enumerate() {
+        let line_end = line_start + line.len();
+        if offset <= line_end || line_end == source.len() {
+            let col = offset.saturating_sub(line_start) + 1;
+            return (num + 1, col, line);
+        }
+        line_start = line_end + 1;
+        line_num = num + 2;
+    }
+
+    (line_num, 1, "")
+}
+
+/// Renders a rustc-style diagnostic for a span on one source line: the
+/// message prefixed with `line:col`, followed by the source line the span
+/// starts on, underlined with carets.
+pub fn render_diagnostic(source: &str, span: Span, message: &str) -> String {
+    if span.is_unknown() {
+        return message.to_string();
+    }
+
+    let (line, col, line_text) = line_col(source, span.start);
+    let width = (span.end.saturating_sub(span.start)).max(1);
+    let underline = " ".repeat(col.saturating_sub(1)) + &"^".repeat(width);
+
+    format!(
+        "{}:{}: {}\n  {}\n  {}",
+        line, col, message, line_text, underline
+    )
+}
@@ -0,0 +1,374 @@
+//! The numeric tower: a `Numeric` value together with the promotion rules
+//! used to make mixed-type arithmetic and comparison well-defined.
+//!
+//! The lattice is `I64 -> BigInt -> Rational -> Decimal -> F64` (`Decimal`
+//! only when the `decimal` feature is on): an operation promotes both
+//! operands to the narrowest common rung before computing, so `i64 + i64`
+//! only ever becomes a `BigInt` on overflow, and anything mixed with a float
+//! becomes a float (as does a `BigInt` mixed with a `Rational`; see
+//! `Numeric::promote`).
+
+use crate::rail_machine::{RailError, RailVal};
+
+/// Greatest common divisor, used to keep rationals in lowest terms. Works in
+/// `u64` magnitudes rather than `i64::abs()`, since `i64::MIN`'s magnitude
+/// doesn't fit back in an `i64` and `.abs()` panics on it.
+fn gcd(a: i64, b: i64) -> u64 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Greatest common divisor over the wider `i128`, used by `rational_from_i128`
+/// to reduce a fraction before checking whether it still fits in `i64`.
+fn gcd128(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Reduces a fraction to lowest terms with a positive denominator. `den` is
+/// always non-zero by the time this is called: every caller either came from
+/// an already-normalized `Rational`/`I64` or checked for a zero divisor
+/// itself (see `Numeric::checked_div`).
+fn normalize_rational(num: i64, den: i64) -> (i64, i64) {
+    debug_assert!(den != 0, "rational denominator cannot be zero");
+    // Widen to i128 before applying the sign flip: negating i64::MIN (a
+    // valid numerator) overflows i64, but not i128.
+    let divisor = gcd(num, den).max(1) as i128;
+    let sign: i128 = if den < 0 { -1 } else { 1 };
+    let num = (num as i128 * sign) / divisor;
+    let den = (den as i128 * sign) / divisor;
+    (num as i64, den as i64)
+}
+
+/// Builds an exact `Rational`/`I64` from an `i128` numerator/denominator pair
+/// too wide for the plain `i64` rational ops to produce directly (e.g. a
+/// product or cross-multiplication that overflowed `i64`), falling back to a
+/// lossy `F64` only if the fraction, once reduced, still doesn't fit `i64` —
+/// there's no wider exact rational rung in this tower. `den` must be
+/// non-zero; callers check for a zero divisor before reaching here.
+fn rational_from_i128(num: i128, den: i128) -> Numeric {
+    debug_assert!(den != 0, "a Rational's denominator is always kept non-zero");
+    let divisor = gcd128(num, den).max(1);
+    let (num, den) = (num / divisor, den / divisor);
+    match (i64::try_from(num), i64::try_from(den)) {
+        (Ok(num), Ok(den)) => Numeric::rational(num, den),
+        _ => Numeric::F64(num as f64 / den as f64),
+    }
+}
+
+fn overflow(op: &str) -> RailError {
+    RailError::Arithmetic(format!("\"{}\" overflowed", op))
+}
+
+fn division_by_zero() -> RailError {
+    RailError::Arithmetic("division by zero".to_string())
+}
+
+/// Renders a fixed-point `mantissa / 10^scale` decimal, e.g. `(150, 2)` as
+/// `"1.50"`.
+#[cfg(feature = "decimal")]
+pub fn format_decimal(mantissa: i128, scale: u32) -> String {
+    if scale == 0 {
+        return mantissa.to_string();
+    }
+    let factor = 10i128.pow(scale);
+    let sign = if mantissa < 0 { "-" } else { "" };
+    let whole = mantissa.unsigned_abs() / factor as u128;
+    let frac = mantissa.unsigned_abs() % factor as u128;
+    format!("{}{}.{:0width$}", sign, whole, frac, width = scale as usize)
+}
+
+/// Scales a decimal mantissa up from `scale` to `new_scale` (which must be
+/// at least as wide), keeping the value it represents unchanged.
+#[cfg(feature = "decimal")]
+fn rescale_decimal(mantissa: i128, scale: u32, new_scale: u32) -> i128 {
+    mantissa * 10i128.pow(new_scale - scale)
+}
+
+/// A value from the numeric tower, already stripped of everything that isn't
+/// a number (booleans, strings, ...).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Numeric {
+    I64(i64),
+    BigInt(i128),
+    /// Always normalized: lowest terms, positive denominator, never an
+    /// integral value (those collapse back to `I64`/`BigInt`).
+    Rational(i64, i64),
+    /// `mantissa` scaled by `10^scale`; see `RailVal::Decimal`.
+    #[cfg(feature = "decimal")]
+    Decimal(i128, u32),
+    F64(f64),
+}
+
+impl Numeric {
+    /// Builds a rational, normalizing it and collapsing to an integer when
+    /// the denominator divides evenly.
+    pub fn rational(num: i64, den: i64) -> Numeric {
+        let (num, den) = normalize_rational(num, den);
+        if den == 1 {
+            Numeric::I64(num)
+        } else {
+            Numeric::Rational(num, den)
+        }
+    }
+
+    pub fn from_rail_val(value: &RailVal) -> Option<Numeric> {
+        match value {
+            RailVal::I64(n) => Some(Numeric::I64(*n)),
+            RailVal::BigInt(n) => Some(Numeric::BigInt(*n)),
+            RailVal::Rational(num, den) => Some(Numeric::rational(*num, *den)),
+            #[cfg(feature = "decimal")]
+            RailVal::Decimal(mantissa, scale) => Some(Numeric::Decimal(*mantissa, *scale)),
+            RailVal::F64(n) => Some(Numeric::F64(*n)),
+            _ => None,
+        }
+    }
+
+    pub fn to_rail_val(self) -> RailVal {
+        match self {
+            Numeric::I64(n) => RailVal::I64(n),
+            Numeric::BigInt(n) => match i64::try_from(n) {
+                Ok(n) => RailVal::I64(n),
+                Err(_) => RailVal::BigInt(n),
+            },
+            Numeric::Rational(num, den) => RailVal::Rational(num, den),
+            #[cfg(feature = "decimal")]
+            Numeric::Decimal(mantissa, scale) => RailVal::Decimal(mantissa, scale),
+            Numeric::F64(n) => RailVal::F64(n),
+        }
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Numeric::I64(n) => n as f64,
+            Numeric::BigInt(n) => n as f64,
+            Numeric::Rational(num, den) => num as f64 / den as f64,
+            #[cfg(feature = "decimal")]
+            Numeric::Decimal(mantissa, scale) => mantissa as f64 / 10f64.powi(scale as i32),
+            Numeric::F64(n) => n,
+        }
+    }
+
+    fn as_rational(self) -> (i64, i64) {
+        match self {
+            Numeric::I64(n) => (n, 1),
+            Numeric::BigInt(n) => (
+                i64::try_from(n).expect("big integer too large to become a rational"),
+                1,
+            ),
+            Numeric::Rational(num, den) => (num, den),
+            #[cfg(feature = "decimal")]
+            Numeric::Decimal(..) => unreachable!("decimals promote past rationals, never to one"),
+            Numeric::F64(_) => unreachable!("floats never promote to rational"),
+        }
+    }
+
+    fn as_big_int(self) -> i128 {
+        match self {
+            Numeric::I64(n) => n as i128,
+            Numeric::BigInt(n) => n,
+            #[cfg(feature = "decimal")]
+            Numeric::Decimal(..) => unreachable!("decimals never promote to big-int"),
+            Numeric::Rational(..) | Numeric::F64(_) => {
+                unreachable!("rationals and floats never promote to big-int")
+            }
+        }
+    }
+
+    /// Lifts any lower rung to a decimal at `scale`, rounding to the nearest
+    /// representable value (only rationals can lose precision here).
+    #[cfg(feature = "decimal")]
+    fn as_decimal(self, scale: u32) -> i128 {
+        match self {
+            Numeric::I64(n) => n as i128 * 10i128.pow(scale),
+            Numeric::BigInt(n) => n * 10i128.pow(scale),
+            Numeric::Rational(num, den) => {
+                ((num as f64 / den as f64) * 10f64.powi(scale as i32)).round() as i128
+            }
+            Numeric::Decimal(mantissa, s) => rescale_decimal(mantissa, s, scale),
+            Numeric::F64(_) => unreachable!("floats never promote to decimal"),
+        }
+    }
+
+    /// Converts any numeric value to a `Decimal` at `scale`, the one way a
+    /// `Decimal` can be produced from outside the tower's own promotion
+    /// (`>decimal`, in `corelib::arithmetic`). Rounds to the nearest
+    /// representable value when the source can't be represented exactly
+    /// (rationals and floats only); unlike `as_decimal`, this also accepts
+    /// an `F64`, which `as_decimal` treats as strictly wider than `Decimal`
+    /// and so never narrows.
+    #[cfg(feature = "decimal")]
+    pub fn to_decimal(self, scale: u32) -> Numeric {
+        match self {
+            Numeric::F64(n) => Numeric::Decimal((n * 10f64.powi(scale as i32)).round() as i128, scale),
+            other => Numeric::Decimal(other.as_decimal(scale), scale),
+        }
+    }
+
+    /// Lifts two numerics to their shared rung on the tower: whichever is
+    /// wider of `I64 < BigInt < Rational < Decimal < F64`. A `BigInt` too
+    /// wide for `i64` mixed with a `Rational` goes to `F64` instead, since a
+    /// `Rational`'s parts are `i64`.
+    fn promote(a: Numeric, b: Numeric) -> (Numeric, Numeric) {
+        use Numeric::*;
+        match (a, b) {
+            (F64(_), _) | (_, F64(_)) => (F64(a.as_f64()), F64(b.as_f64())),
+            #[cfg(feature = "decimal")]
+            (Decimal(..), _) | (_, Decimal(..)) => {
+                let scale = match (a, b) {
+                    (Decimal(_, s), Decimal(_, t)) => s.max(t),
+                    (Decimal(_, s), _) | (_, Decimal(_, s)) => s,
+                    _ => unreachable!("one side is always a Decimal here"),
+                };
+                (Decimal(a.as_decimal(scale), scale), Decimal(b.as_decimal(scale), scale))
+            }
+            (Rational(..), BigInt(n)) | (BigInt(n), Rational(..)) if i64::try_from(n).is_err() => {
+                (F64(a.as_f64()), F64(b.as_f64()))
+            }
+            (Rational(..), _) | (_, Rational(..)) => {
+                let (an, ad) = a.as_rational();
+                let (bn, bd) = b.as_rational();
+                (Rational(an, ad), Rational(bn, bd))
+            }
+            (BigInt(_), _) | (_, BigInt(_)) => (BigInt(a.as_big_int()), BigInt(b.as_big_int())),
+            (I64(x), I64(y)) => (I64(x), I64(y)),
+        }
+    }
+
+    pub fn checked_add(self, other: Numeric) -> Result<Numeric, RailError> {
+        match Numeric::promote(self, other) {
+            (Numeric::I64(a), Numeric::I64(b)) => Ok(match a.checked_add(b) {
+                Some(sum) => Numeric::I64(sum),
+                None => Numeric::BigInt(a as i128 + b as i128),
+            }),
+            (Numeric::BigInt(a), Numeric::BigInt(b)) => {
+                a.checked_add(b).map(Numeric::BigInt).ok_or_else(|| overflow("+"))
+            }
+            (Numeric::Rational(an, ad), Numeric::Rational(bn, bd)) => Ok(rational_from_i128(
+                an as i128 * bd as i128 + bn as i128 * ad as i128,
+                ad as i128 * bd as i128,
+            )),
+            #[cfg(feature = "decimal")]
+            (Numeric::Decimal(am, s), Numeric::Decimal(bm, _)) => {
+                am.checked_add(bm).map(|sum| Numeric::Decimal(sum, s)).ok_or_else(|| overflow("+"))
+            }
+            (Numeric::F64(a), Numeric::F64(b)) => Ok(Numeric::F64(a + b)),
+            _ => unreachable!("promote always returns a matching pair"),
+        }
+    }
+
+    pub fn checked_sub(self, other: Numeric) -> Result<Numeric, RailError> {
+        self.checked_add(other.negate())
+    }
+
+    pub fn negate(self) -> Numeric {
+        match self {
+            Numeric::I64(n) => n.checked_neg().map(Numeric::I64).unwrap_or(Numeric::BigInt(-(n as i128))),
+            Numeric::BigInt(n) => Numeric::BigInt(-n),
+            Numeric::Rational(num, den) => Numeric::Rational(-num, den),
+            #[cfg(feature = "decimal")]
+            Numeric::Decimal(mantissa, scale) => Numeric::Decimal(-mantissa, scale),
+            Numeric::F64(n) => Numeric::F64(-n),
+        }
+    }
+
+    pub fn checked_mul(self, other: Numeric) -> Result<Numeric, RailError> {
+        match Numeric::promote(self, other) {
+            (Numeric::I64(a), Numeric::I64(b)) => Ok(match a.checked_mul(b) {
+                Some(product) => Numeric::I64(product),
+                None => Numeric::BigInt(a as i128 * b as i128),
+            }),
+            (Numeric::BigInt(a), Numeric::BigInt(b)) => {
+                a.checked_mul(b).map(Numeric::BigInt).ok_or_else(|| overflow("*"))
+            }
+            (Numeric::Rational(an, ad), Numeric::Rational(bn, bd)) => {
+                Ok(rational_from_i128(an as i128 * bn as i128, ad as i128 * bd as i128))
+            }
+            #[cfg(feature = "decimal")]
+            (Numeric::Decimal(am, scale), Numeric::Decimal(bm, _)) => am
+                .checked_mul(bm)
+                .map(|product| Numeric::Decimal(product / 10i128.pow(scale), scale))
+                .ok_or_else(|| overflow("*")),
+            (Numeric::F64(a), Numeric::F64(b)) => Ok(Numeric::F64(a * b)),
+            _ => unreachable!("promote always returns a matching pair"),
+        }
+    }
+
+    /// Integer division that doesn't divide evenly yields a `Rational`
+    /// rather than truncating; a `BigInt` whose exact quotient doesn't fit
+    /// back into `i64` falls back to `F64` (see `rational_from_i128`).
+    pub fn checked_div(self, other: Numeric) -> Result<Numeric, RailError> {
+        match Numeric::promote(self, other) {
+            (Numeric::I64(a), Numeric::I64(b)) => {
+                if b == 0 {
+                    return Err(division_by_zero());
+                }
+                Ok(Numeric::rational(a, b))
+            }
+            (Numeric::BigInt(a), Numeric::BigInt(b)) => {
+                if b == 0 {
+                    return Err(division_by_zero());
+                }
+                Ok(if a % b == 0 {
+                    Numeric::BigInt(a / b)
+                } else {
+                    rational_from_i128(a, b)
+                })
+            }
+            (Numeric::Rational(an, ad), Numeric::Rational(bn, bd)) => {
+                if bn == 0 {
+                    return Err(division_by_zero());
+                }
+                Ok(rational_from_i128(an as i128 * bd as i128, ad as i128 * bn as i128))
+            }
+            #[cfg(feature = "decimal")]
+            (Numeric::Decimal(am, scale), Numeric::Decimal(bm, _)) => {
+                if bm == 0 {
+                    return Err(division_by_zero());
+                }
+                am.checked_mul(10i128.pow(scale))
+                    .and_then(|scaled| scaled.checked_div(bm))
+                    .map(|result| Numeric::Decimal(result, scale))
+                    .ok_or_else(|| overflow("/"))
+            }
+            (Numeric::F64(a), Numeric::F64(b)) => Ok(Numeric::F64(a / b)),
+            _ => unreachable!("promote always returns a matching pair"),
+        }
+    }
+
+    pub fn partial_cmp(self, other: Numeric) -> Option<std::cmp::Ordering> {
+        match Numeric::promote(self, other) {
+            (Numeric::I64(a), Numeric::I64(b)) => a.partial_cmp(&b),
+            (Numeric::BigInt(a), Numeric::BigInt(b)) => a.partial_cmp(&b),
+            (Numeric::Rational(an, ad), Numeric::Rational(bn, bd)) => {
+                // Widen to i128 before cross-multiplying, same as add/mul/div:
+                // two in-range i64 rationals can still cross-multiply past
+                // i64's range.
+                (an as i128 * bd as i128).partial_cmp(&(bn as i128 * ad as i128))
+            }
+            #[cfg(feature = "decimal")]
+            (Numeric::Decimal(am, _), Numeric::Decimal(bm, _)) => am.partial_cmp(&bm),
+            (Numeric::F64(a), Numeric::F64(b)) => a.partial_cmp(&b),
+            _ => unreachable!("promote always returns a matching pair"),
+        }
+    }
+}
+
+impl std::fmt::Display for Numeric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Numeric::I64(n) => write!(f, "{}", n),
+            Numeric::BigInt(n) => write!(f, "{}", n),
+            Numeric::Rational(num, den) => write!(f, "{}/{}", num, den),
+            #[cfg(feature = "decimal")]
+            Numeric::Decimal(mantissa, scale) => write!(f, "{}", format_decimal(*mantissa, *scale)),
+            Numeric::F64(n) => write!(f, "{}", n),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! A line-buffering REPL driver: reads `dt` source from stdin a line at a
+//! time, emitting a continuation prompt while a quotation or string is left
+//! open, and only handing the accumulated buffer to the interpreter once it
+//! reads as balanced. This mirrors Schala's multiline REPL entry, and avoids
+//! having to cram a whole quotation onto one line.
+//!
+//! `rail_machine::Context` is what ultimately tracks "are we still inside a
+//! quotation" once a program is running, but this tree's tokenizer lexes a
+//! `"..."` string as a single atomic token and has no comment syntax, so
+//! there's no `Context::String`/`Context::Comment` to drive off of here —
+//! the buffer just needs to know whether it has an unclosed `[` or `"`
+//! before it's worth tokenizing at all.
+
+use crate::dt_machine::DtState;
+use std::io::{self, BufRead, Write};
+
+const PROMPT: &str = "> ";
+const CONTINUATION_PROMPT: &str = "... ";
+
+/// Runs an interactive REPL against `state` until stdin closes.
+pub fn run(mut state: DtState) -> DtState {
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut buffer = String::new();
+
+    loop {
+        print!("{}", prompt_for(&buffer));
+        io::stdout().flush().ok();
+
+        let line = match lines.next() {
+            Some(line) => line.unwrap_or_else(|err| panic!("Could not read from stdin: {}", err)),
+            None => break,
+        };
+
+        if !buffer.is_empty() {
+            buffer.push('\n');
+        }
+        buffer.push_str(&line);
+
+        if unclosed_constructs(&buffer) > 0 {
+            continue;
+        }
+
+        state = state.run_source(&buffer);
+        buffer.clear();
+    }
+
+    state
+}
+
+fn prompt_for(buffer: &str) -> &'static str {
+    if buffer.is_empty() {
+        PROMPT
+    } else {
+        CONTINUATION_PROMPT
+    }
+}
+
+/// How many constructs a fresh read of `buffer` would still leave open: each
+/// unmatched `[`, plus one for an unclosed `"..."` string; `0` once every `[`
+/// has a matching `]` and any string has been closed. A stray `]` with
+/// nothing open is skipped rather than counted against a later `[` — there's
+/// nothing more reading another line could do to fix it, and the parser
+/// drops it the same way.
+fn unclosed_constructs(buffer: &str) -> i64 {
+    let mut depth: i64 = 0;
+    let mut in_string = false;
+    let mut chars = buffer.chars();
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_string = true,
+            '[' => depth += 1,
+            ']' => depth = (depth - 1).max(0),
+            _ => {}
+        }
+    }
+
+    depth + i64::from(in_string)
+}
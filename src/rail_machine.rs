@@ -1,4 +1,7 @@
 use crate::corelib::new_dictionary;
+use crate::loading::{render_diagnostic, Span};
+use crate::numeric::Numeric;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::Arc;
@@ -8,7 +11,14 @@ pub struct RailState {
     // TODO: Provide update functions and make these private
     pub stack: Quote,
     pub dictionary: Dictionary,
+    /// Parse-time macros registered by `def-syntax`, consulted by
+    /// `loading::parse_one_term` after tokenizing, before the evaluator ever
+    /// sees a registered marker. See `SyntaxRule`.
+    pub syntax: SyntaxTable,
     pub context: Context,
+    /// The original source text being evaluated, kept around so diagnostics
+    /// can render the offending line. Empty when there is no source to show.
+    pub source: Arc<str>,
 }
 
 impl RailState {
@@ -18,7 +28,16 @@ impl RailState {
         RailState {
             stack,
             dictionary,
+            syntax: SyntaxTable::new(),
             context,
+            source: Arc::from(""),
+        }
+    }
+
+    pub fn with_source(self, source: impl Into<Arc<str>>) -> RailState {
+        RailState {
+            source: source.into(),
+            ..self
         }
     }
 
@@ -26,7 +45,9 @@ impl RailState {
         RailState {
             stack: update(self.stack),
             dictionary: self.dictionary,
+            syntax: self.syntax,
             context: self.context,
+            source: self.source,
         }
     }
 
@@ -38,15 +59,67 @@ impl RailState {
         RailState {
             stack,
             dictionary,
+            syntax: self.syntax,
             context: self.context,
+            source: self.source,
         }
     }
 
+    /// Like `update_stack`, but for an update that can fail; the `RailState`
+    /// carried on `Err` is whatever the caller already held onto, not a
+    /// partially-updated one, since `update` never got to return one.
+    pub fn try_update_stack(
+        self,
+        update: impl Fn(Quote) -> Result<Quote, RailError>,
+    ) -> Result<RailState, RailError> {
+        let stack = update(self.stack)?;
+        Ok(RailState {
+            stack,
+            dictionary: self.dictionary,
+            syntax: self.syntax,
+            context: self.context,
+            source: self.source,
+        })
+    }
+
+    /// Like `update_stack_and_dict`, but for an update that can fail.
+    pub fn try_update_stack_and_dict(
+        self,
+        update: impl Fn(Quote, Dictionary) -> Result<(Quote, Dictionary), RailError>,
+    ) -> Result<RailState, RailError> {
+        let (stack, dictionary) = update(self.stack, self.dictionary)?;
+        Ok(RailState {
+            stack,
+            dictionary,
+            syntax: self.syntax,
+            context: self.context,
+            source: self.source,
+        })
+    }
+
+    /// Like `try_update_stack_and_dict`, but threading the syntax table
+    /// instead of the dictionary; used by `def-syntax`.
+    pub fn try_update_stack_and_syntax(
+        self,
+        update: impl Fn(Quote, SyntaxTable) -> Result<(Quote, SyntaxTable), RailError>,
+    ) -> Result<RailState, RailError> {
+        let (stack, syntax) = update(self.stack, self.syntax)?;
+        Ok(RailState {
+            stack,
+            dictionary: self.dictionary,
+            syntax,
+            context: self.context,
+            source: self.source,
+        })
+    }
+
     pub fn replace_stack(self, stack: Quote) -> RailState {
         RailState {
             stack,
             dictionary: self.dictionary,
+            syntax: self.syntax,
             context: self.context,
+            source: self.source,
         }
     }
 
@@ -54,7 +127,9 @@ impl RailState {
         RailState {
             stack,
             dictionary: self.dictionary.clone(),
+            syntax: self.syntax.clone(),
             context: Context::None,
+            source: self.source.clone(),
         }
     }
 
@@ -66,7 +141,9 @@ impl RailState {
         RailState {
             stack: Quote::new(),
             dictionary: self.dictionary,
+            syntax: self.syntax,
             context,
+            source: self.source,
         }
     }
 
@@ -89,7 +166,9 @@ impl RailState {
         RailState {
             stack,
             dictionary: self.dictionary,
+            syntax: self.syntax,
             context,
+            source: self.source,
         }
     }
 }
@@ -100,7 +179,108 @@ impl Default for RailState {
     }
 }
 
-#[derive(Clone, Debug)]
+/// A serializable snapshot of a `RailState`: its stack, its source text, and
+/// its user-defined (`def`'d) words. The rest of the dictionary and the
+/// `Context` call-frame nesting are intentionally left out: builtins hold a
+/// `Fn` closure that can never be serialized, and a resumed session always
+/// starts back at the top level — the way the Dust AST keeps its tree
+/// `Serialize`/`Deserialize` while skipping the non-serializable execution
+/// context.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RailStateSnapshot {
+    pub stack: Quote,
+    pub source: String,
+    /// User-defined words, as `(name, body)` pairs. `Dictionary` is a
+    /// `HashMap`, so these aren't necessarily in definition order;
+    /// `RailState::from_snapshot` restores them in rounds so order doesn't
+    /// matter. Only a word whose dependency is missing altogether fails to
+    /// restore, and that failure is reported, not fatal.
+    pub definitions: Vec<(String, Quote)>,
+    /// User-registered `def-syntax` rules, as `(marker, rule)` pairs.
+    pub syntax: Vec<(String, SyntaxRule)>,
+}
+
+impl RailState {
+    /// Captures everything needed to resume this session later: see
+    /// `RailStateSnapshot`.
+    pub fn to_snapshot(&self) -> RailStateSnapshot {
+        let definitions = self
+            .dictionary
+            .values()
+            .filter_map(|def| {
+                def.quotation_body()
+                    .map(|quote| (def.name.clone(), quote.clone()))
+            })
+            .collect();
+
+        let syntax = self
+            .syntax
+            .iter()
+            .map(|(marker, rule)| (marker.clone(), rule.clone()))
+            .collect();
+
+        RailStateSnapshot {
+            stack: self.stack.clone(),
+            source: self.source.to_string(),
+            definitions,
+            syntax,
+        }
+    }
+
+    /// Rebuilds a `RailState` from a snapshot: a fresh dictionary (builtins
+    /// plus whatever user-defined words restore cleanly) and `Context::Main`.
+    ///
+    /// `snapshot.definitions` isn't necessarily in dependency order (it comes
+    /// out of a `HashMap`), so a word defined in terms of another user word
+    /// restored later in the list would fail on a single top-to-bottom pass.
+    /// Retry in rounds instead, restoring whatever's possible each round,
+    /// until a round restores nothing more; only words still unresolved at
+    /// that point (a genuinely missing dependency, not just ordering) are
+    /// reported as failed.
+    pub fn from_snapshot(snapshot: RailStateSnapshot) -> RailState {
+        let mut dictionary = new_dictionary();
+        let mut pending = snapshot.definitions;
+
+        loop {
+            let mut failures = vec![];
+            let mut restored_any = false;
+
+            for (name, quote) in pending {
+                match RailDef::from_quote(&name, quote.clone(), &dictionary) {
+                    Ok(def) => {
+                        dictionary.insert(name, Arc::new(def));
+                        restored_any = true;
+                    }
+                    Err(err) => failures.push((name, quote, err)),
+                }
+            }
+
+            if !restored_any {
+                for (name, _, err) in failures {
+                    eprintln!(
+                        "Derailed: could not restore \"{}\" from snapshot: {}",
+                        name, err
+                    );
+                }
+                break;
+            }
+
+            pending = failures.into_iter().map(|(name, quote, _)| (name, quote)).collect();
+        }
+
+        let syntax = snapshot.syntax.into_iter().collect();
+
+        RailState {
+            stack: snapshot.stack,
+            dictionary,
+            syntax,
+            context: Context::Main,
+            source: Arc::from(snapshot.source),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Context {
     Main,
     Quotation {
@@ -110,13 +290,28 @@ pub enum Context {
     None,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum RailVal {
     Boolean(bool),
-    // TODO: Make a "Numeric" typeclass. (And floating-point/rational numbers)
     I64(i64),
+    /// Only ever holds a value that overflows `i64`; see `crate::numeric`.
+    BigInt(i128),
+    /// Always normalized: lowest terms, positive denominator. Construct
+    /// through `Numeric::rational`, which collapses integral results back
+    /// down to `I64` instead of producing e.g. `4/2`.
+    Rational(i64, i64),
+    /// Fixed-point, for money-style math that can't tolerate `F64` rounding.
+    /// `mantissa` scaled by `10^scale`, e.g. `Decimal(150, 2)` is `1.50`.
+    /// Gated behind the `decimal` feature so builds that don't need it (and
+    /// 32-bit targets, where the `i128` mantissa is pricier) can skip it,
+    /// the way Rhai gates its own `decimal` feature.
+    #[cfg(feature = "decimal")]
+    Decimal(i128, u32),
     F64(f64),
-    Command(String),
+    /// Reference-counted so cloning it around the stack is cheap. Names are
+    /// not interned: each parsed occurrence holds its own allocation, and a
+    /// dictionary lookup still hashes the name.
+    Command(Arc<str>),
     Quote(Quote),
     String(String),
 }
@@ -127,6 +322,10 @@ impl RailVal {
         match self {
             Boolean(_) => "bool",
             I64(_) => "i64",
+            BigInt(_) => "bigint",
+            Rational(..) => "rational",
+            #[cfg(feature = "decimal")]
+            Decimal(..) => "decimal",
             F64(_) => "f64",
             Command(_) => "command",
             Quote(_) => "quote",
@@ -142,6 +341,10 @@ impl std::fmt::Display for RailVal {
         match self {
             Boolean(b) => write!(fmt, "{}", if *b { "true" } else { "false" }),
             I64(n) => write!(fmt, "{}", n),
+            BigInt(n) => write!(fmt, "{}", n),
+            Rational(num, den) => write!(fmt, "{}/{}", num, den),
+            #[cfg(feature = "decimal")]
+            Decimal(mantissa, scale) => write!(fmt, "{}", crate::numeric::format_decimal(*mantissa, *scale)),
             F64(n) => write!(fmt, "{}", n),
             Command(o) => write!(fmt, "{}", o),
             Quote(q) => write!(fmt, "{}", q),
@@ -150,14 +353,53 @@ impl std::fmt::Display for RailVal {
     }
 }
 
-#[derive(Clone, Debug)]
+impl PartialEq for RailVal {
+    fn eq(&self, other: &Self) -> bool {
+        match (Numeric::from_rail_val(self), Numeric::from_rail_val(other)) {
+            (Some(a), Some(b)) => a.partial_cmp(b) == Some(std::cmp::Ordering::Equal),
+            _ => match (self, other) {
+                (RailVal::Boolean(a), RailVal::Boolean(b)) => a == b,
+                (RailVal::Command(a), RailVal::Command(b)) => a == b,
+                (RailVal::Quote(a), RailVal::Quote(b)) => a == b,
+                (RailVal::String(a), RailVal::String(b)) => a == b,
+                _ => false,
+            },
+        }
+    }
+}
+
+impl PartialOrd for RailVal {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match (Numeric::from_rail_val(self), Numeric::from_rail_val(other)) {
+            (Some(a), Some(b)) => a.partial_cmp(b),
+            _ => None,
+        }
+    }
+}
+
+/// `dt`'s stack/quotation value. Both `values` and `spans` live behind an
+/// `Arc` so cloning a `Quote` (which happens constantly — every nested
+/// `RailVal::Quote` clone, every dictionary lookup result) is a pointer
+/// bump rather than a deep copy. Mutating methods go through
+/// `Arc::make_mut`, so a `Quote` that's uniquely held is mutated in place,
+/// and one that's still shared (e.g. referenced elsewhere on the stack) is
+/// copied on that first write only.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Quote {
-    pub values: Vec<RailVal>,
+    pub values: Arc<Vec<RailVal>>,
+    /// Byte-offset source span for each entry in `values`, kept in lockstep
+    /// so `values[i]`'s span is `spans[i]`. Entries that didn't come from
+    /// source text (builtin results, `def`'s generated quotations, ...) get
+    /// `Span::unknown()`.
+    pub spans: Arc<Vec<Span>>,
 }
 
 impl Quote {
     pub fn new() -> Self {
-        Quote { values: vec![] }
+        Quote {
+            values: Arc::new(vec![]),
+            spans: Arc::new(vec![]),
+        }
     }
 
     pub fn len(&self) -> usize {
@@ -168,92 +410,141 @@ impl Quote {
         self.values.is_empty()
     }
 
-    pub fn push(mut self, term: RailVal) -> Quote {
-        self.values.push(term);
+    /// Reverses both the values and their paired spans in place.
+    pub fn reverse(mut self) -> Quote {
+        Arc::make_mut(&mut self.values).reverse();
+        Arc::make_mut(&mut self.spans).reverse();
         self
     }
 
-    pub fn push_bool(mut self, b: bool) -> Quote {
-        self.values.push(RailVal::Boolean(b));
-        self
+    pub fn push(self, term: RailVal) -> Quote {
+        self.push_spanned(term, Span::unknown())
     }
 
-    pub fn push_i64(mut self, i: i64) -> Quote {
-        self.values.push(RailVal::I64(i));
+    pub fn push_spanned(mut self, term: RailVal, span: Span) -> Quote {
+        Arc::make_mut(&mut self.values).push(term);
+        Arc::make_mut(&mut self.spans).push(span);
         self
     }
 
-    pub fn push_f64(mut self, n: f64) -> Quote {
-        self.values.push(RailVal::F64(n));
-        self
+    pub fn push_bool(self, b: bool) -> Quote {
+        self.push(RailVal::Boolean(b))
     }
 
-    pub fn push_command(mut self, op_name: &str) -> Quote {
-        self.values.push(RailVal::Command(op_name.to_owned()));
-        self
+    pub fn push_i64(self, i: i64) -> Quote {
+        self.push(RailVal::I64(i))
     }
 
-    pub fn push_quote(mut self, quote: Quote) -> Quote {
-        self.values.push(RailVal::Quote(quote));
-        self
+    pub fn push_f64(self, n: f64) -> Quote {
+        self.push(RailVal::F64(n))
     }
 
-    pub fn push_string(mut self, s: String) -> Quote {
-        self.values.push(RailVal::String(s));
-        self
+    pub fn push_command(self, op_name: &str) -> Quote {
+        self.push(RailVal::Command(Arc::from(op_name)))
     }
 
-    pub fn push_str(mut self, s: &str) -> Quote {
-        self.values.push(RailVal::String(s.to_owned()));
-        self
+    pub fn push_quote(self, quote: Quote) -> Quote {
+        self.push(RailVal::Quote(quote))
     }
 
-    pub fn pop(mut self) -> (RailVal, Quote) {
-        let term = self.values.pop().unwrap();
-        (term, self)
+    pub fn push_string(self, s: String) -> Quote {
+        self.push(RailVal::String(s))
     }
 
-    pub fn pop_bool(mut self, context: &str) -> (bool, Quote) {
-        match self.values.pop().unwrap() {
-            RailVal::Boolean(b) => (b, self),
-            rail_val => panic!("{}", type_panic_msg(context, "bool", rail_val)),
+    pub fn push_str(self, s: &str) -> Quote {
+        self.push(RailVal::String(s.to_owned()))
+    }
+
+    /// Pops the top value and the span it was pushed with, or a
+    /// `StackUnderflow` if the stack is empty. Shared by the typed `pop_*`
+    /// helpers below, which additionally check the popped value's variant
+    /// and, on a mismatch, point the error at that value's own span rather
+    /// than just naming the word that rejected it.
+    fn pop_any(mut self, context: &str) -> Result<(RailVal, Span, Quote), RailError> {
+        match Arc::make_mut(&mut self.values).pop() {
+            Some(term) => {
+                let span = Arc::make_mut(&mut self.spans).pop().unwrap_or_else(Span::unknown);
+                Ok((term, span, self))
+            }
+            None => Err(RailError::StackUnderflow {
+                op: context.to_string(),
+                wanted: ANY_TYPE.to_string(),
+                span: Span::unknown(),
+            }),
         }
     }
 
-    pub fn pop_i64(mut self, context: &str) -> (i64, Quote) {
-        match self.values.pop().unwrap() {
-            RailVal::I64(n) => (n, self),
-            rail_val => panic!("{}", type_panic_msg(context, "i64", rail_val)),
+    pub fn pop(self) -> Result<(RailVal, Quote), RailError> {
+        let (term, _span, quote) = self.pop_any("pop")?;
+        Ok((term, quote))
+    }
+
+    pub fn pop_bool(self, context: &str) -> Result<(bool, Quote), RailError> {
+        let (term, span, quote) = self.pop_any(context)?;
+        match term {
+            RailVal::Boolean(b) => Ok((b, quote)),
+            rail_val => Err(type_mismatch(context, "bool", rail_val, span)),
         }
     }
 
-    pub fn pop_f64(mut self, context: &str) -> (f64, Quote) {
-        match self.values.pop().unwrap() {
-            RailVal::F64(n) => (n, self),
-            rail_val => panic!("{}", type_panic_msg(context, "f64", rail_val)),
+    pub fn pop_i64(self, context: &str) -> Result<(i64, Quote), RailError> {
+        let (term, span, quote) = self.pop_any(context)?;
+        match term {
+            RailVal::I64(n) => Ok((n, quote)),
+            rail_val => Err(type_mismatch(context, "i64", rail_val, span)),
         }
     }
 
-    fn _pop_command(mut self, context: &str) -> (String, Quote) {
-        match self.values.pop().unwrap() {
-            RailVal::Command(op) => (op, self),
-            rail_val => panic!("{}", type_panic_msg(context, "command", rail_val)),
+    pub fn pop_f64(self, context: &str) -> Result<(f64, Quote), RailError> {
+        let (term, span, quote) = self.pop_any(context)?;
+        match term {
+            RailVal::F64(n) => Ok((n, quote)),
+            rail_val => Err(type_mismatch(context, "f64", rail_val, span)),
         }
     }
 
-    pub fn pop_quote(mut self, context: &str) -> (Quote, Quote) {
-        match self.values.pop().unwrap() {
-            RailVal::Quote(quote) => (quote, self),
-            rail_val => panic!("{}", type_panic_msg(context, "quote", rail_val)),
+    fn _pop_command(self, context: &str) -> Result<(Arc<str>, Quote), RailError> {
+        let (term, span, quote) = self.pop_any(context)?;
+        match term {
+            RailVal::Command(op) => Ok((op, quote)),
+            rail_val => Err(type_mismatch(context, "command", rail_val, span)),
         }
     }
 
-    pub fn pop_string(mut self, context: &str) -> (String, Quote) {
-        match self.values.pop().unwrap() {
-            RailVal::String(s) => (s, self),
-            rail_val => panic!("{}", type_panic_msg(context, "string", rail_val)),
+    pub fn pop_quote(self, context: &str) -> Result<(Quote, Quote), RailError> {
+        let (term, span, quote) = self.pop_any(context)?;
+        match term {
+            RailVal::Quote(inner) => Ok((inner, quote)),
+            rail_val => Err(type_mismatch(context, "quote", rail_val, span)),
         }
     }
+
+    pub fn pop_string(self, context: &str) -> Result<(String, Quote), RailError> {
+        let (term, span, quote) = self.pop_any(context)?;
+        match term {
+            RailVal::String(s) => Ok((s, quote)),
+            rail_val => Err(type_mismatch(context, "string", rail_val, span)),
+        }
+    }
+
+    /// Pops any value from the numeric tower (`i64`, big-int, rational,
+    /// decimal, or `f64`), regardless of which one it happens to be.
+    pub fn pop_numeric(self, context: &str) -> Result<(Numeric, Quote), RailError> {
+        let (term, span, quote) = self.pop_any(context)?;
+        match term {
+            RailVal::I64(n) => Ok((Numeric::I64(n), quote)),
+            RailVal::BigInt(n) => Ok((Numeric::BigInt(n), quote)),
+            RailVal::Rational(num, den) => Ok((Numeric::rational(num, den), quote)),
+            #[cfg(feature = "decimal")]
+            RailVal::Decimal(mantissa, scale) => Ok((Numeric::Decimal(mantissa, scale), quote)),
+            RailVal::F64(n) => Ok((Numeric::F64(n), quote)),
+            rail_val => Err(type_mismatch(context, "numeric", rail_val, span)),
+        }
+    }
+
+    pub fn push_numeric(self, n: Numeric) -> Quote {
+        self.push(n.to_rail_val())
+    }
 }
 
 impl Default for Quote {
@@ -262,11 +553,17 @@ impl Default for Quote {
     }
 }
 
+impl PartialEq for Quote {
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values
+    }
+}
+
 impl std::fmt::Display for Quote {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(f, "[ ").unwrap();
 
-        for term in &self.values {
+        for term in self.values.iter() {
             write!(f, "{} ", term).unwrap();
         }
 
@@ -276,26 +573,121 @@ impl std::fmt::Display for Quote {
     }
 }
 
-pub fn type_panic_msg(context: &str, expected: &str, actual: RailVal) -> String {
-    format!(
-        "[Context: {}] Wanted {}, but got {}",
-        context, expected, actual
-    )
+fn type_mismatch(context: &str, expected: &str, actual: RailVal, span: Span) -> RailError {
+    RailError::TypeMismatch {
+        op: context.to_string(),
+        wanted: expected.to_string(),
+        got: actual.type_name(),
+        span,
+    }
+}
+
+/// A recoverable interpreter failure. Carried back to the caller instead of
+/// aborting the process, so a driver (a REPL, `try`) can report it, roll
+/// back to the stack snapshot it held before the failing command ran, and
+/// keep going.
+#[derive(Clone, Debug)]
+pub enum RailError {
+    StackUnderflow {
+        op: String,
+        wanted: String,
+        span: Span,
+    },
+    TypeMismatch {
+        op: String,
+        wanted: String,
+        got: String,
+        span: Span,
+    },
+    Undefined {
+        name: String,
+        span: Span,
+    },
+    /// A `def` body whose net stack effect couldn't be inferred.
+    Effect(EffectError),
+    /// A real OS-level failure (stdin/stdout) from an I/O operator like
+    /// `read-line` or `each-line`, carried back instead of panicking so
+    /// `try` can catch a broken pipe the same as any other derail.
+    Io(String),
+    /// A numeric operation that has no valid result to give back: division
+    /// by zero, or a result too wide for every rung of the numeric tower
+    /// that operation could land on.
+    Arithmetic(String),
+}
+
+impl RailError {
+    pub fn span(&self) -> Span {
+        match self {
+            RailError::StackUnderflow { span, .. } => *span,
+            RailError::TypeMismatch { span, .. } => *span,
+            RailError::Undefined { span, .. } => *span,
+            RailError::Effect(_) => Span::unknown(),
+            RailError::Io(_) => Span::unknown(),
+            RailError::Arithmetic(_) => Span::unknown(),
+        }
+    }
+}
+
+impl std::fmt::Display for RailError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            RailError::StackUnderflow { op, wanted, .. } => {
+                write!(f, "Derailed: stack underflow for \"{}\": wanted {}", op, wanted)
+            }
+            RailError::TypeMismatch { op, wanted, got, .. } => write!(
+                f,
+                "Derailed: type mismatch for \"{}\": wanted {}, but got {}",
+                op, wanted, got
+            ),
+            RailError::Undefined { name, .. } => write!(f, "Derailed: \"{}\" is undefined", name),
+            RailError::Effect(err) => write!(f, "Derailed: could not infer effect: {}", err),
+            RailError::Io(msg) => write!(f, "Derailed: I/O error: {}", msg),
+            RailError::Arithmetic(msg) => write!(f, "Derailed: {}", msg),
+        }
+    }
+}
+
+/// Looking up a word and invoking it shouldn't have to clone its whole
+/// definition (including a user-defined word's entire quotation body), so
+/// the dictionary holds each one behind an `Arc`; `act`/`act_at` take `&self`
+/// and a lookup hit is just a reference, not a copy.
+pub type Dictionary = HashMap<String, Arc<RailDef<'static>>>;
+
+/// A parse-time macro registered by `def-syntax`: when the parser
+/// (`loading::parse_one_term`) meets the marker word this rule is keyed
+/// under, it keeps consuming raw tokens up to (and including) `terminator`.
+/// The first captured token becomes a name and the rest becomes a quoted
+/// body, rewritten to `[ <rest> ] "<name>" def` before the evaluator ever
+/// sees it — enough to turn a colon-definition like `: double dup + ;` into
+/// an ordinary `def` call.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SyntaxRule {
+    pub terminator: String,
 }
 
-pub type Dictionary = HashMap<String, RailDef<'static>>;
+/// Keyed by marker word, consulted by `loading::parse_one_term` as it scans.
+pub type SyntaxTable = HashMap<String, SyntaxRule>;
+
+/// The wildcard type name that unifies with anything during effect checking.
+const ANY_TYPE: &str = "*";
+
+/// The placeholder a builtin's `produces` uses when its effect depends on a
+/// runtime quotation or argument (e.g. `do`, `try`, `each-line`) and so can't
+/// be named statically at all — distinct from `ANY_TYPE`, which still means
+/// exactly one value of some type, just not a specific one.
+const DYNAMIC_EFFECT: &str = "...";
 
 #[derive(Clone)]
 pub struct RailDef<'a> {
     pub name: String,
-    consumes: &'a [&'a str],
-    produces: &'a [&'a str],
+    consumes: Vec<String>,
+    produces: Vec<String>,
     action: RailAction<'a>,
 }
 
 #[derive(Clone)]
 pub enum RailAction<'a> {
-    Builtin(Arc<dyn Fn(RailState) -> RailState + 'a>),
+    Builtin(Arc<dyn Fn(RailState) -> Result<RailState, RailError> + Send + Sync + 'a>),
     Quotation(Quote),
 }
 
@@ -307,12 +699,12 @@ impl RailDef<'_> {
         state_action: F,
     ) -> RailDef<'a>
     where
-        F: Fn(RailState) -> RailState + 'a,
+        F: Fn(RailState) -> Result<RailState, RailError> + Send + Sync + 'a,
     {
         RailDef {
             name: name.to_string(),
-            consumes,
-            produces,
+            consumes: consumes.iter().map(|s| s.to_string()).collect(),
+            produces: produces.iter().map(|s| s.to_string()).collect(),
             action: RailAction::Builtin(Arc::new(state_action)),
         }
     }
@@ -324,10 +716,10 @@ impl RailDef<'_> {
         stack_action: F,
     ) -> RailDef<'a>
     where
-        F: Fn(Quote) -> Quote + 'a,
+        F: Fn(Quote) -> Result<Quote, RailError> + Send + Sync + 'a,
     {
         RailDef::on_state(name, consumes, produces, move |state| {
-            state.update_stack(&stack_action)
+            state.try_update_stack(&stack_action)
         })
     }
 
@@ -338,38 +730,84 @@ impl RailDef<'_> {
         contextless_action: F,
     ) -> RailDef<'a>
     where
-        F: Fn() + 'a,
+        F: Fn() + Send + Sync + 'a,
     {
         RailDef::on_state(name, consumes, produces, move |state| {
             contextless_action();
-            state
+            Ok(state)
         })
     }
 
-    pub fn from_quote<'a>(name: &str, quote: Quote) -> RailDef<'a> {
-        // TODO: Infer stack effects
-        RailDef {
+    pub fn from_quote<'a>(
+        name: &str,
+        quote: Quote,
+        dictionary: &Dictionary,
+    ) -> Result<RailDef<'a>, RailError> {
+        // A self-recursive body (e.g. `: countdown 1 - countdown ;`) calls
+        // `name` before `name` has an effect to look up, since it isn't in
+        // `dictionary` yet at all. Infer against a copy of `dictionary` that
+        // already has a wildcard-effect placeholder for `name`, so a
+        // recursive call unifies with anything rather than coming back
+        // `Undefined`; the real, inferred effect replaces it below.
+        let mut provisional_dictionary = dictionary.clone();
+        provisional_dictionary.insert(
+            name.to_string(),
+            Arc::new(RailDef {
+                name: name.to_string(),
+                consumes: vec![ANY_TYPE.to_string()],
+                produces: vec![ANY_TYPE.to_string()],
+                action: RailAction::Quotation(Quote::new()),
+            }),
+        );
+
+        let effect = infer_effect(&quote, &provisional_dictionary)?;
+
+        Ok(RailDef {
             name: name.to_string(),
-            consumes: &[],
-            produces: &[],
+            consumes: effect.consumes,
+            produces: effect.produces,
             action: RailAction::Quotation(quote),
+        })
+    }
+
+    /// The quotation body backing a user-defined (`def`'d) word, or `None`
+    /// for a builtin, whose `Fn` closure can't be serialized or otherwise
+    /// inspected. Used by `RailState::to_snapshot` to decide what's worth
+    /// saving.
+    pub fn quotation_body(&self) -> Option<&Quote> {
+        match &self.action {
+            RailAction::Quotation(quote) => Some(quote),
+            RailAction::Builtin(_) => None,
         }
     }
 
-    pub fn act(&mut self, state: RailState) -> RailState {
+    pub fn act(&self, state: RailState) -> Result<RailState, RailError> {
+        self.act_at(state, Span::unknown())
+    }
+
+    /// Same as `act`, but `span` locates the command in source so a derail
+    /// can point at the exact offending token instead of just naming it.
+    pub fn act_at(&self, state: RailState, span: Span) -> Result<RailState, RailError> {
         if state.stack.len() < self.consumes.len() {
-            // TODO: At some point will want source context here like line/column number.
-            eprintln!(
-                "Derailed: stack underflow for \"{}\" ({} -> {}): stack only had {}",
-                self.name,
-                self.consumes.join(" "),
-                self.produces.join(" "),
-                state.stack.len()
-            );
-            std::process::exit(1);
+            return Err(RailError::StackUnderflow {
+                op: self.name.clone(),
+                wanted: self.consumes.join(" "),
+                span,
+            });
         }
 
-        // TODO: Type checks
+        let top = &state.stack.values[state.stack.len() - self.consumes.len()..];
+        for (consumed, actual) in self.consumes.iter().zip(top.iter()) {
+            let actual_type = actual.type_name();
+            if consumed != ANY_TYPE && *consumed != actual_type {
+                return Err(RailError::TypeMismatch {
+                    op: self.name.clone(),
+                    wanted: consumed.clone(),
+                    got: actual_type,
+                    span,
+                });
+            }
+        }
 
         match &self.action {
             RailAction::Builtin(action) => action(state),
@@ -378,6 +816,13 @@ impl RailDef<'_> {
     }
 }
 
+/// Renders a `RailError` against `state`'s source and prints it, the way a
+/// REPL or `dt`'s top-level driver reports a failed command without
+/// aborting the process.
+pub fn report_error(state: &RailState, err: &RailError) {
+    eprintln!("{}", render_diagnostic(&state.source, err.span(), &err.to_string()));
+}
+
 impl Debug for RailDef<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         write!(
@@ -390,18 +835,161 @@ impl Debug for RailDef<'_> {
     }
 }
 
-pub fn run_quote(quote: &Quote, state: RailState) -> RailState {
+/// The net stack effect of a quotation: the types it needs on entry and the
+/// types it leaves on exit, bottom of stack first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Effect {
+    pub consumes: Vec<String>,
+    pub produces: Vec<String>,
+}
+
+/// A conflict found while statically verifying or inferring a quotation's effect.
+#[derive(Clone, Debug)]
+pub struct EffectError {
+    pub word: String,
+    pub index: usize,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl std::fmt::Display for EffectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "at term {} (\"{}\"): wanted {}, but got {}",
+            self.index, self.word, self.expected, self.actual
+        )
+    }
+}
+
+/// Abstract stack used to simulate a quotation's effect without running it.
+/// Popping past the bottom doesn't fail; it instead records the type that
+/// would have had to come from the caller, so `infer_effect` can report it
+/// as a required input.
+struct AbstractStack {
+    stack: Vec<String>,
+    inputs: Vec<String>,
+}
+
+impl AbstractStack {
+    fn new() -> Self {
+        AbstractStack {
+            stack: vec![],
+            inputs: vec![],
+        }
+    }
+
+    fn push(&mut self, type_name: String) {
+        self.stack.push(type_name)
+    }
+
+    fn pop(&mut self, wanted: &str) -> String {
+        match self.stack.pop() {
+            Some(actual) => actual,
+            None => {
+                self.inputs.insert(0, wanted.to_string());
+                wanted.to_string()
+            }
+        }
+    }
+}
+
+fn unifies(wanted: &str, actual: &str) -> bool {
+    wanted == ANY_TYPE
+        || actual == ANY_TYPE
+        || wanted == DYNAMIC_EFFECT
+        || actual == DYNAMIC_EFFECT
+        || wanted == actual
+}
+
+fn simulate_effect(quote: &Quote, dictionary: &Dictionary) -> Result<AbstractStack, RailError> {
+    let mut abstract_stack = AbstractStack::new();
+
+    for (index, term) in quote.values.iter().enumerate() {
+        match term {
+            RailVal::Command(op_name) => {
+                let op = dictionary.get(op_name.as_ref()).ok_or_else(|| RailError::Undefined {
+                    name: op_name.to_string(),
+                    span: Span::unknown(),
+                })?;
+
+                for wanted in op.consumes.iter().rev() {
+                    let actual = abstract_stack.pop(wanted);
+                    if !unifies(wanted, &actual) {
+                        return Err(RailError::Effect(EffectError {
+                            word: op_name.to_string(),
+                            index,
+                            expected: wanted.clone(),
+                            actual,
+                        }));
+                    }
+                }
+
+                for produced in &op.produces {
+                    abstract_stack.push(produced.clone());
+                }
+            }
+            other => abstract_stack.push(other.type_name()),
+        }
+    }
+
+    Ok(abstract_stack)
+}
+
+/// Infers the net stack effect of a user-defined word's body, so `from_quote`
+/// never has to fall back to an empty `&[]`/`&[]` signature.
+pub fn infer_effect(quote: &Quote, dictionary: &Dictionary) -> Result<Effect, RailError> {
+    let abstract_stack = simulate_effect(quote, dictionary)?;
+    Ok(Effect {
+        consumes: abstract_stack.inputs,
+        produces: abstract_stack.stack,
+    })
+}
+
+/// Statically verifies a top-level quotation, catching stack underflow and
+/// type mismatches before it is ever run. Unlike `infer_effect`, underflow
+/// here is a real error: there is no caller left to supply the missing value.
+pub fn verify_quote(quote: &Quote, dictionary: &Dictionary) -> Result<Effect, RailError> {
+    let effect = infer_effect(quote, dictionary)?;
+    if let Some(index) = effect.consumes.first() {
+        return Err(RailError::Effect(EffectError {
+            word: quote
+                .values
+                .first()
+                .map(|v| v.type_name())
+                .unwrap_or_else(|| "quote".to_string()),
+            index: 0,
+            expected: index.clone(),
+            actual: "nothing (stack underflow)".to_string(),
+        }));
+    }
+    Ok(effect)
+}
+
+/// Runs `quote` against `state`, stopping at the first `RailError`. Since
+/// each step only ever hands back a brand new `RailState` on success, an
+/// `Err` carries no partially-applied state for the caller to worry about
+/// rolling back; the caller's own pre-call `state` is simply never replaced.
+pub fn run_quote(quote: &Quote, state: RailState) -> Result<RailState, RailError> {
     quote
         .values
         .iter()
-        .fold(state, |state, rail_val| match rail_val {
+        .zip(quote.spans.iter())
+        .try_fold(state, |state, (rail_val, span)| match rail_val {
             RailVal::Command(op_name) => {
                 let op = state
                     .dictionary
-                    .get(&op_name.clone())
-                    .unwrap_or_else(|| panic!("Tried to do \"{}\" but it was undefined", op_name));
-                op.clone().act(state)
+                    .get(op_name.as_ref())
+                    .cloned()
+                    .ok_or_else(|| RailError::Undefined {
+                        name: op_name.to_string(),
+                        span: *span,
+                    })?;
+                op.act_at(state, *span)
+            }
+            _ => {
+                let span = *span;
+                Ok(state.update_stack(|stack| stack.push_spanned(rail_val.clone(), span)))
             }
-            _ => state.update_stack(|stack| stack.push(rail_val.clone())),
         })
 }
@@ -0,0 +1,45 @@
+mod dt_test_utils;
+use dt_test_utils::dt_oneliner;
+
+#[test]
+fn test_try_recovers_a_derailing_quote_by_pushing_the_error_string() {
+    let res = dt_oneliner("1 [ 5 0 / ] try println");
+    assert_eq!("", &res.stderr);
+    assert_eq!("Derailed: division by zero\n", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_try_leaves_the_stack_exactly_as_the_quote_would_have_on_success() {
+    let res = dt_oneliner("1 [ 2 + ] try print");
+    assert_eq!("", &res.stderr);
+    assert_eq!("3", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_try_rolls_back_anything_the_quote_pushed_before_it_derailed() {
+    // try restores the stack the quote would have seen, not one still
+    // holding whatever the quote itself pushed before failing partway
+    // through its own body.
+    let res = dt_oneliner("1 [ 99 5 0 / ] try println");
+    assert_eq!("", &res.stderr);
+    assert_eq!("Derailed: division by zero\n", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_opt_runs_the_action_for_the_first_true_condition() {
+    let res = dt_oneliner("[ [ false ] [ 1 ] [ true ] [ 2 ] ] opt print");
+    assert_eq!("", &res.stderr);
+    assert_eq!("2", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_opt_does_nothing_when_no_condition_matches() {
+    let res = dt_oneliner("[ [ false ] [ 1 ] ] opt 42 print");
+    assert_eq!("", &res.stderr);
+    assert_eq!("42", &res.stdout);
+    assert!(res.status.success());
+}
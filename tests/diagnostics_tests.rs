@@ -0,0 +1,29 @@
+mod dt_test_utils;
+use dt_test_utils::dt_oneliner;
+
+#[test]
+fn test_type_mismatch_diagnostic_underlines_the_offending_value_not_the_operator() {
+    // pop_numeric's type_mismatch carries the popped value's own span, not
+    // the call site of the word that rejected it, so the caret should land
+    // under "true" at column 3, not under "+" at column 8.
+    let res = dt_oneliner("1 true +");
+    assert_eq!("", &res.stdout);
+    assert_eq!(
+        "1:3: Derailed: type mismatch for \"+\": wanted numeric, but got bool\n  1 true +\n    ^^^^\n",
+        &res.stderr
+    );
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_type_mismatch_diagnostic_moves_with_the_offending_token() {
+    // Same mismatch, but the bad value is the first operand this time — the
+    // caret should track it to column 1, not stay pinned to a fixed offset.
+    let res = dt_oneliner("true 1 +");
+    assert_eq!("", &res.stdout);
+    assert_eq!(
+        "1:1: Derailed: type mismatch for \"+\": wanted numeric, but got bool\n  true 1 +\n  ^^^^\n",
+        &res.stderr
+    );
+    assert!(res.status.success());
+}
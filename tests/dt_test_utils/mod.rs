@@ -0,0 +1,71 @@
+//! Shared helper for `tests/*.rs`: spawns the actual compiled `dt` binary,
+//! so these are end-to-end tests of the real CLI rather than calls into the
+//! library. Living under `dt_test_utils/mod.rs` (rather than a bare
+//! `dt_test_utils.rs`) keeps Cargo from treating this file as its own test
+//! binary; it only exists to be `mod`'d in by the real test files.
+//!
+//! Each `tests/*.rs` file is its own crate with its own copy of this module,
+//! and only ever calls the subset of these helpers it needs — so whichever
+//! one a given file doesn't call trips `dead_code` there under `-D warnings`.
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+pub struct DtOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub status: std::process::ExitStatus,
+}
+
+/// Runs `dt` with `args` as separate command-line arguments, the way a
+/// shell would pass them, and captures its output.
+pub fn dt(args: &[&str]) -> DtOutput {
+    let output = Command::new(env!("CARGO_BIN_EXE_dt"))
+        .args(args)
+        .output()
+        .unwrap_or_else(|err| panic!("could not run dt: {}", err));
+
+    DtOutput {
+        stdout: String::from_utf8(output.stdout).expect("dt's stdout wasn't valid UTF-8"),
+        stderr: String::from_utf8(output.stderr).expect("dt's stderr wasn't valid UTF-8"),
+        status: output.status,
+    }
+}
+
+/// Runs `dt` with `code` as a single argument, the way a shell one-liner
+/// like `dt "1 2 + print"` would.
+pub fn dt_oneliner(code: &str) -> DtOutput {
+    dt(&[code])
+}
+
+/// Runs `dt` with `args`, feeding `input` in on stdin and closing it once
+/// written, then captures its output — for the REPL (no `dt_code` args) and
+/// the I/O words (`read-line`, `read-all`, `each-line`) that read from the
+/// real stdin.
+pub fn dt_with_stdin(args: &[&str], input: &str) -> DtOutput {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dt"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|err| panic!("could not spawn dt: {}", err));
+
+    child
+        .stdin
+        .take()
+        .expect("dt's stdin wasn't piped")
+        .write_all(input.as_bytes())
+        .unwrap_or_else(|err| panic!("could not write to dt's stdin: {}", err));
+
+    let output = child
+        .wait_with_output()
+        .unwrap_or_else(|err| panic!("could not wait on dt: {}", err));
+
+    DtOutput {
+        stdout: String::from_utf8(output.stdout).expect("dt's stdout wasn't valid UTF-8"),
+        stderr: String::from_utf8(output.stderr).expect("dt's stderr wasn't valid UTF-8"),
+        status: output.status,
+    }
+}
@@ -0,0 +1,94 @@
+mod dt_test_utils;
+use dt_test_utils::{dt, dt_oneliner};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+#[test]
+fn test_check_prints_inferred_effect_for_a_valid_file() {
+    let file = write_temp_dt_file("1 2 +");
+
+    let res = dt(&["check", file.path.as_str()]);
+    assert_eq!("", &res.stderr);
+    assert_eq!(" -> *\n", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_check_reports_an_undefined_word_without_running_anything() {
+    let file = write_temp_dt_file("totally-undefined-word");
+
+    let res = dt(&["check", file.path.as_str()]);
+    assert_eq!("", &res.stdout);
+    assert!(res.stderr.contains("\"totally-undefined-word\" is undefined"));
+    assert!(!res.status.success());
+}
+
+#[test]
+fn test_def_rejects_a_body_referencing_an_undefined_word() {
+    // def verifies the quote it's given before installing it; this used to
+    // panic instead of derailing.
+    let res = dt(&["[", "totally-undefined-word", "]", "\"foo\"", "def"]);
+    assert_eq!("", &res.stdout);
+    assert!(res.stderr.contains("\"totally-undefined-word\" is undefined"));
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_def_allows_a_self_recursive_body() {
+    // The body calls the very word being defined, the normal way to write a
+    // loop in a concatenative language — `def` used to derail with
+    // "countdown" is undefined, since it inferred the new word's effect by
+    // looking it up before insertion.
+    let res = dt_oneliner(r#"[ 1 - countdown ] "countdown" def "countdown" def? print"#);
+    assert_eq!("", &res.stderr);
+    assert_eq!("true", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_check_allows_composing_a_dynamic_effect_combinator_with_a_typed_word() {
+    // `do`'s produces is `["..."]`, a placeholder for "can't know statically"
+    // — `unifies` used to only special-case `"*"`, so this false-positived as
+    // a type mismatch even though running it works fine.
+    let file = write_temp_dt_file("[ true ] do not");
+
+    let res = dt(&["check", file.path.as_str()]);
+    assert_eq!("", &res.stderr);
+    assert_eq!(" -> bool\n", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_check_sees_a_def_syntax_marker_registered_earlier_in_the_same_file() {
+    // `check` used to parse the whole file against one fixed, empty syntax
+    // table, so a marker the file registers for itself was never seen by the
+    // time it was used later on — even though running the same file works.
+    let file = write_temp_dt_file("\":\" \";\" def-syntax\n: inc 1 + ;");
+
+    let res = dt(&["check", file.path.as_str()]);
+    assert_eq!("", &res.stderr);
+    assert!(res.status.success());
+}
+
+/// A `.dt` file under the OS temp dir holding `source`, removed on drop.
+struct TempDtFile {
+    path: String,
+}
+
+impl Drop for TempDtFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn write_temp_dt_file(source: &str) -> TempDtFile {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let path = std::env::temp_dir()
+        .join(format!("dt-effect-test-{}-{}.dt", std::process::id(), id))
+        .to_str()
+        .expect("temp path wasn't valid UTF-8")
+        .to_string();
+
+    std::fs::write(&path, source).expect("could not write temp dt file");
+    TempDtFile { path }
+}
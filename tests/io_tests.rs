@@ -0,0 +1,50 @@
+mod dt_test_utils;
+use dt_test_utils::{dt, dt_oneliner, dt_with_stdin};
+
+#[test]
+fn test_read_line_reports_eof_once_stdin_is_exhausted() {
+    let res = dt_with_stdin(&["read-line println read-line println"], "hello\n");
+    assert_eq!("", &res.stderr);
+    assert_eq!("false\ntrue\n", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_read_all_returns_the_whole_stdin_contents_as_one_string() {
+    let res = dt_with_stdin(&["read-all print"], "line1\nline2\n");
+    assert_eq!("", &res.stderr);
+    assert_eq!("line1\nline2\n", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_each_line_runs_the_quote_once_per_input_line() {
+    let res = dt_with_stdin(&["[ println ] each-line"], "a\nb\nc\n");
+    assert_eq!("", &res.stderr);
+    assert_eq!("a\nb\nc\n", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_print_writes_to_stdout_without_a_trailing_newline() {
+    let res = dt_oneliner(r#""hi" print "there" print"#);
+    assert_eq!("", &res.stderr);
+    assert_eq!("hithere", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_println_writes_to_stdout_with_a_trailing_newline() {
+    let res = dt_oneliner(r#""hi" println"#);
+    assert_eq!("", &res.stderr);
+    assert_eq!("hi\n", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_eprint_writes_to_stderr_without_a_trailing_newline() {
+    let res = dt(&["--", "\"oops\" eprint"]);
+    assert_eq!("", &res.stdout);
+    assert_eq!("oops", &res.stderr);
+    assert!(res.status.success());
+}
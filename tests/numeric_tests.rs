@@ -0,0 +1,101 @@
+mod dt_test_utils;
+use dt_test_utils::{dt, dt_oneliner};
+
+#[test]
+fn test_i64_overflow_promotes_to_bigint() {
+    let res = dt_oneliner("9223372036854775807 1 + print");
+    assert_eq!("", &res.stderr);
+    assert_eq!("9223372036854775808", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_bigint_overflow_derails_instead_of_panicking() {
+    // Three BigInts (each forced past i64 by an overflowing add) multiplied
+    // together overflows even i128, the widest rung below promoting out to
+    // a lossy float — this used to panic rather than derail.
+    let res = dt_oneliner(
+        "9223372036854775807 1 + 9223372036854775807 1 + * 9223372036854775807 1 + * print",
+    );
+    assert_eq!("", &res.stdout);
+    assert!(res.stderr.contains("overflowed"));
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_division_by_zero_derails_instead_of_panicking() {
+    let res = dt_oneliner("5 0 / print");
+    assert_eq!("", &res.stdout);
+    assert!(res.stderr.contains("division by zero"));
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_uneven_division_promotes_to_exact_rational() {
+    assert_eq!("1/3", &dt_oneliner("1 3 / print").stdout);
+
+    // Same uneven division, but with BigInt operands (forced past i64 by an
+    // overflowing add first) — BigInt/BigInt division used to drop straight
+    // to a lossy F64 instead of the exact Rational an I64/I64 division of
+    // the same ratio produces.
+    assert_eq!(
+        "1/3",
+        &dt_oneliner("9223372036854775807 1 + 3 * 9223372036854775807 1 + 9 * / print").stdout
+    );
+}
+
+#[test]
+fn test_even_bigint_division_stays_exact() {
+    assert_eq!(
+        "2",
+        &dt_oneliner("9223372036854775807 1 + 2 * 9223372036854775807 1 + / print").stdout
+    );
+}
+
+#[test]
+fn test_rational_addition_overflow_derails_instead_of_panicking() {
+    // 9223372036854775807/3 + 1/2 used to overflow the i64 cross-multiply
+    // inside Numeric::checked_add's Rational case and panic.
+    let res = dt_oneliner("9223372036854775807 3 / 1 2 / + print");
+    assert_eq!("", &res.stderr);
+    assert!(res.status.success());
+    assert!(!res.stdout.is_empty());
+}
+
+#[test]
+fn test_rational_comparison_overflow_does_not_panic() {
+    // Cross-multiplying two in-range rationals' numerators/denominators in
+    // plain i64 can itself overflow even though neither rational does.
+    let res = dt_oneliner("6000000001 6000000002 / 6000000003 6000000004 / < print");
+    assert_eq!("", &res.stderr);
+    assert_eq!("true", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+#[cfg(feature = "decimal")]
+fn test_to_decimal_converts_integers_and_floats_to_a_fixed_point_value() {
+    // Nothing in the language could ever produce a Decimal before >decimal
+    // existed, despite the whole feature-gated tower compiling.
+    assert_eq!("150.00", &dt_oneliner("150 2 >decimal print").stdout);
+    assert_eq!("1.50", &dt_oneliner("1.5 2 >decimal print").stdout);
+}
+
+#[test]
+#[cfg(feature = "decimal")]
+fn test_to_decimal_rejects_a_negative_scale() {
+    let res = dt_oneliner("5 -1 >decimal print");
+    assert_eq!("", &res.stdout);
+    assert!(res.stderr.contains("scale must not be negative"));
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_dividing_i64_min_does_not_panic() {
+    // i64::MIN has no positive counterpart in i64, so negating it while
+    // normalizing the resulting rational's sign used to overflow.
+    let res = dt(&["--", "-9223372036854775808 3 / print"]);
+    assert_eq!("", &res.stderr);
+    assert_eq!("-9223372036854775808/3", &res.stdout);
+    assert!(res.status.success());
+}
@@ -0,0 +1,32 @@
+mod dt_test_utils;
+use dt_test_utils::dt_with_stdin;
+
+#[test]
+fn test_repl_runs_each_line_as_its_own_term_once_balanced() {
+    let res = dt_with_stdin(&[], "1 2 +\nprint\n");
+    assert_eq!("", &res.stderr);
+    assert_eq!("> > 3> ", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_repl_shows_a_continuation_prompt_until_an_open_quote_is_closed() {
+    // `[ 1` and `2` alone aren't balanced yet, so the REPL must keep
+    // buffering (and prompting `... `) across both lines before it's worth
+    // handing anything to the interpreter.
+    let res = dt_with_stdin(&[], "[ 1\n2\n+ ]\ndo\nprint\n");
+    assert_eq!("", &res.stderr);
+    assert_eq!("> ... ... > > 3> ", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_repl_shows_a_continuation_prompt_until_an_open_string_is_closed() {
+    // A `"..."` string can itself contain a real newline once its two lines
+    // are joined back together, so the buffer has to track "are we inside a
+    // string" separately from bracket depth.
+    let res = dt_with_stdin(&[], "\"hello\nworld\"\nprint\n");
+    assert_eq!("", &res.stderr);
+    assert_eq!("> ... > hello\nworld> ", &res.stdout);
+    assert!(res.status.success());
+}
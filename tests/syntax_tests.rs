@@ -0,0 +1,44 @@
+mod dt_test_utils;
+use dt_test_utils::dt_oneliner;
+
+#[test]
+fn test_def_syntax_registered_earlier_is_usable_later_in_the_same_script() {
+    let res = dt_oneliner(r#"":" ";" def-syntax : inc 1 + ; 41 inc print"#);
+    assert_eq!("", &res.stderr);
+    assert_eq!("42", &res.stdout);
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_a_marker_used_before_it_is_registered_stays_undefined() {
+    // ":" isn't registered as a def-syntax marker until after this term is
+    // parsed, so it must be left alone rather than expanded.
+    let res = dt_oneliner(r#": double 1 + ;"#);
+    assert_eq!("", &res.stdout);
+    assert!(res.stderr.contains("\":\" is undefined"));
+    assert!(res.status.success());
+}
+
+#[test]
+fn test_def_syntax_still_works_across_separate_invocations_via_snapshot() {
+    let dump_path = std::env::temp_dir()
+        .join(format!("dt-syntax-test-{}.json", std::process::id()))
+        .to_str()
+        .expect("temp path wasn't valid UTF-8")
+        .to_string();
+
+    let define = dt_test_utils::dt(&[
+        "--dump",
+        &dump_path,
+        r#"":" ";" def-syntax : inc 1 + ;"#,
+    ]);
+    assert_eq!("", &define.stderr);
+    assert!(define.status.success());
+
+    let use_it = dt_test_utils::dt(&["--resume", &dump_path, "41 inc print"]);
+    assert_eq!("", &use_it.stderr);
+    assert_eq!("42", &use_it.stdout);
+    assert!(use_it.status.success());
+
+    let _ = std::fs::remove_file(&dump_path);
+}